@@ -1,5 +1,9 @@
 #![no_std]
-use aidoku::{alloc::borrow::Cow, prelude::*, Source};
+use aidoku::{
+	alloc::{vec, Vec},
+	prelude::*,
+	Filter, SelectFilter, Source,
+};
 use mangareader::{Impl, MangaReader, Params};
 
 const BASE_URL: &str = "https://mangamura.net";
@@ -17,9 +21,10 @@ impl Impl for MangaMura {
 			search_path: "".into(),
 			search_param: "q".into(),
 			page_param: "p".into(),
-			get_chapter_selector: || "#ja-chaps > li".into(),
-			get_chapter_language: |_| "ja".into(),
+			chapter_list_selector: "[id$=-chaps]".into(),
 			get_page_url_path: |chapter_id| format!("/json/chapter?id={chapter_id}&mode=vertical"),
+			get_related_listing_name: |title| format!("{title}の関連作品"),
+			get_fallback_filters: fallback_filters,
 			set_default_filters: |query_params| {
 				query_params.set("type", Some("all"));
 				query_params.set("status", Some("all"));
@@ -29,22 +34,61 @@ impl Impl for MangaMura {
 			..Default::default()
 		}
 	}
+}
 
-	fn get_sort_id(&self, index: i32) -> Cow<'static, str> {
-		match index {
-			0 => "default",
-			1 => "latest-update",
-			2 => "most-viewed",
-			3 => "title-az",
-			4 => "title-za",
-			_ => "default",
+// the site's sort ids differ from mangareader's, so they're kept for when the filter form fails
+fn fallback_filters() -> Vec<Filter> {
+	vec![
+		SelectFilter {
+			id: "sort".into(),
+			title: Some("選別".into()),
+			options: vec![
+				"デフォルト".into(),
+				"最新の更新".into(),
+				"最も見られました".into(),
+				"Title [A-Z]".into(),
+				"Title [Z-A]".into(),
+			],
+			ids: Some(vec![
+				"default".into(),
+				"latest-update".into(),
+				"most-viewed".into(),
+				"title-az".into(),
+				"title-za".into(),
+			]),
+			..Default::default()
 		}
-		.into()
-	}
+		.into(),
+		SelectFilter {
+			id: "type".into(),
+			title: Some("タイプ".into()),
+			options: vec![
+				"全て".into(),
+				"Raw Manga".into(),
+				"BLコミック".into(),
+				"TLコミック".into(),
+				"オトナコミック".into(),
+				"女性マンガ".into(),
+				"少女マンガ".into(),
+				"少年マンガ".into(),
+				"青年マンガ".into(),
+			],
+			..Default::default()
+		}
+		.into(),
+		SelectFilter {
+			id: "status".into(),
+			title: Some("地位".into()),
+			options: vec!["全て".into(), "Publishing".into(), "Finished".into()],
+			..Default::default()
+		}
+		.into(),
+	]
 }
 
 register_source!(
 	MangaReader<MangaMura>,
+	DynamicFilters,
 	ListingProvider,
 	Home,
 	ImageRequestProvider,
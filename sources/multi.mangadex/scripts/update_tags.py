@@ -1,44 +1,75 @@
-# curl https://api.mangadex.org/manga/tag | jq "[.data[] | { type: \"genre\", name: .attributes.name.en, id: .id, canExclude: true }]"
-import dataclasses
-import subprocess
+# updates the bundled fallback tags (src/tags.json) and the blocked tags setting
+# (res/settings.json) from https://api.mangadex.org/manga/tag
+#
+# settings.json is static, so the blocked tags setting can't be built from the tags
+# fetched at runtime. both files are generated here so they stay in sync.
+#
+# usage: python3 scripts/update_tags.py [--bundled]
+#   --bundled: regenerate the setting from the existing src/tags.json without fetching
 import json
 import os
+import re
 import shutil
+import subprocess
+import sys
 
-class EnhancedJSONEncoder(json.JSONEncoder):
-    def default(self, o):
-        if dataclasses.is_dataclass(o):
-            return dataclasses.asdict(o)
-        return super().default(o)
-
+root = os.path.join(os.path.dirname(os.path.realpath(__file__)), "..")
+tags_json = os.path.join(root, "src", "tags.json")
+settings_json = os.path.join(root, "res", "settings.json")
 
-if not shutil.which("curl"):
-    raise Exception("curl is not installed")
+if "--bundled" in sys.argv:
+    with open(tags_json, "r") as f:
+        tags = json.load(f)
+else:
+    if not shutil.which("curl"):
+        raise Exception("curl is not installed")
+    response = json.loads(
+        subprocess.check_output(
+            ["curl", "-sL", "https://api.mangadex.org/manga/tag?limit=100"]
+        )
+    )
+    tags = [
+        {
+            "id": tag["id"],
+            "group": tag["attributes"]["group"],
+            "name": {"en": tag["attributes"]["name"]["en"]},
+        }
+        for tag in response["data"]
+    ]
 
-tags = json.loads(
-    subprocess.check_output(["curl", "-sL", "https://api.mangadex.org/manga/tag"])
+groups = ["content", "format", "genre", "theme"]
+tags = sorted(
+    tags,
+    key=lambda tag: (
+        groups.index(tag["group"]) if tag["group"] in groups else len(groups),
+        tag["name"]["en"].lower(),
+    ),
 )
 
-filters_json = os.path.join(
-    os.path.dirname(os.path.realpath(__file__)), "..", "res", "filters.json"
-)
-with open(filters_json, "r") as f:
-    filters = json.load(f)
-    for filter in filters:
-        name = filter.get("title")
-        if name in ["Content", "Format", "Genre", "Theme"]:
-            items = sorted(
-                [
-                    (tag["attributes"]["name"]["en"], tag["id"])
-                    for tag in tags["data"]
-                    if tag["attributes"]["group"] == name.lower()
-                ],
-                key=lambda x: x[0].lower(),
-            )
-            filter["options"] = [item[0] for item in items]
-            filter["ids"] = [item[1] for item in items]
-
-
-with open(filters_json, "w") as f:
-    json.dump(filters, f, indent="\t", cls=EnhancedJSONEncoder)
+with open(tags_json, "w") as f:
+    json.dump(tags, f, indent="\t", ensure_ascii=False)
     f.write("\n")
+
+# only the blocked tags item is replaced, to keep the rest of the file's formatting
+by_name = sorted(tags, key=lambda tag: tag["name"]["en"].lower())
+
+
+def json_list(items):
+    indent = "\t" * 5
+    return "[\n" + ",\n".join(f"{indent}{json.dumps(item)}" for item in items) + "\n\t\t\t\t]"
+
+
+with open(settings_json, "r") as f:
+    settings = f.read()
+settings, count = re.subn(
+    r'("key": "blockedTags",\n\t+"title": "[^"]*",\n\t+"values": )\[[^\]]*\](,\n\t+"titles": )\[[^\]]*\]',
+    lambda m: m.group(1)
+    + json_list([tag["id"] for tag in by_name])
+    + m.group(2)
+    + json_list([tag["name"]["en"] for tag in by_name]),
+    settings,
+)
+if count != 1:
+    raise Exception("blocked tags setting not found in settings.json")
+with open(settings_json, "w") as f:
+    f.write(settings)
@@ -0,0 +1,278 @@
+// loading chapter pages from mangadex@home, with health reports and fallbacks
+// (https://api.mangadex.org/docs/04-chapter/retrieving-chapter/)
+use crate::{models::DexAtHomeResponse, net, settings, API_URL};
+use aidoku::{
+	alloc::{string::ToString, String, Vec},
+	imports::{canvas::ImageRef, error::AidokuError, net::Request, std::current_date},
+	prelude::*,
+	ImageResponse, Page, PageContent, PageContext, Result,
+};
+use serde::{Deserialize, Serialize};
+
+const REPORT_URL: &str = "https://api.mangadex.network/report";
+const UPLOADS_URL: &str = "https://uploads.mangadex.org";
+
+// at-home base urls are only valid for a limited time after they're fetched
+const SERVER_TTL: i64 = 60 * 15;
+
+// request header holding when a page image request was created, to time failed loads
+const REQUESTED_AT_HEADER: &str = "X-Requested-At";
+
+// page context keys
+const CHAPTER_KEY: &str = "chapter";
+const HASH_KEY: &str = "hash";
+const FILE_KEY: &str = "file";
+const QUALITY_KEY: &str = "quality";
+const FETCHED_AT_KEY: &str = "fetchedAt";
+
+// the at-home server last fetched for a chapter, stored in defaults until it expires
+#[derive(Default, Deserialize, Serialize, Debug, Clone)]
+pub struct CachedServer {
+	pub chapter_id: String,
+	pub base_url: String,
+	pub fetched_at: i64,
+	// whether the server was already replaced after a failed page
+	#[serde(default)]
+	pub refetched: bool,
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+	url: &'a str,
+	success: bool,
+	cached: bool,
+	bytes: usize,
+	duration: i64,
+}
+
+// fetch a chapter's at-home server, or none if the chapter was removed
+fn fetch_server(chapter_id: &str, refetched: bool) -> Result<Option<DexAtHomeResponse>> {
+	let url = format!(
+		"{API_URL}/at-home/server/{chapter_id}{}",
+		if settings::get_force_port() {
+			"?forcePort443=true"
+		} else {
+			""
+		}
+	);
+	let response = Request::get(url)?.send();
+	if response
+		.as_ref()
+		.is_ok_and(|response| matches!(response.status_code(), 404 | 410))
+	{
+		return Ok(None);
+	}
+	let response = net::check_response(response)?.get_json::<DexAtHomeResponse>()?;
+	// servers are kept per chapter, so loading several chapters at once doesn't reset
+	// the retry state of another
+	let now = current_date();
+	let mut servers = settings::get_at_home_servers();
+	servers
+		.retain(|server| server.chapter_id != chapter_id && now - server.fetched_at < SERVER_TTL);
+	servers.push(CachedServer {
+		chapter_id: chapter_id.into(),
+		base_url: response.base_url.clone(),
+		fetched_at: now,
+		refetched,
+	});
+	settings::set_at_home_servers(&servers);
+	Ok(Some(response))
+}
+
+// get the last fetched server for a chapter if it hasn't expired
+fn get_cached_server(chapter_id: &str) -> Option<CachedServer> {
+	settings::get_at_home_servers().into_iter().find(|server| {
+		server.chapter_id == chapter_id && current_date() - server.fetched_at < SERVER_TTL
+	})
+}
+
+// get a base url for a chapter that hasn't expired, reusing the last fetched server
+fn get_base_url(chapter_id: &str) -> Result<String> {
+	if let Some(server) = get_cached_server(chapter_id) {
+		return Ok(server.base_url);
+	}
+	fetch_server(chapter_id, false)?
+		.map(|response| response.base_url)
+		.ok_or(AidokuError::message("Chapter is unavailable"))
+}
+
+// get a base url to retry a failed page with, replacing the failed server at most once
+// per chapter to stay within the at-home server endpoint's rate limit
+fn get_retry_base_url(chapter_id: &str, failed_url: Option<&str>) -> Option<String> {
+	match get_cached_server(chapter_id) {
+		// another page already replaced the server that failed
+		Some(server) if failed_url.is_some_and(|url| !url.starts_with(&server.base_url)) => {
+			Some(server.base_url)
+		}
+		Some(server) if server.refetched => None,
+		_ => fetch_server(chapter_id, true)
+			.ok()
+			.flatten()
+			.map(|response| response.base_url),
+	}
+}
+
+// get the pages of a chapter, or none if mangadex no longer serves them
+pub fn get_page_list(chapter_id: &str) -> Result<Option<Vec<Page>>> {
+	let Some(response) = fetch_server(chapter_id, false)? else {
+		return Ok(None);
+	};
+	let fetched_at = current_date().to_string();
+
+	let data_saver = settings::get_data_saver();
+	let quality = if data_saver { "dataSaver" } else { "data" };
+	let chapter = response.chapter;
+	let files = if data_saver {
+		chapter.data_saver
+	} else {
+		chapter.data
+	}
+	.ok_or(AidokuError::message("Missing chapter data"))?;
+	if files.is_empty() {
+		return Ok(None);
+	}
+
+	Ok(Some(
+		files
+			.into_iter()
+			.map(|file| {
+				let mut context = PageContext::new();
+				context.insert(CHAPTER_KEY.into(), chapter_id.into());
+				context.insert(HASH_KEY.into(), chapter.hash.clone());
+				context.insert(FILE_KEY.into(), file.clone());
+				context.insert(QUALITY_KEY.into(), quality.into());
+				context.insert(FETCHED_AT_KEY.into(), fetched_at.clone());
+				Page {
+					content: PageContent::url_context(
+						format!("{}/{quality}/{}/{file}", response.base_url, chapter.hash),
+						context,
+					),
+					..Default::default()
+				}
+			})
+			.collect(),
+	))
+}
+
+// the path of a page image relative to an at-home base url
+fn page_path(context: &PageContext) -> Option<String> {
+	Some(format!(
+		"{}/{}/{}",
+		context.get(QUALITY_KEY)?,
+		context.get(HASH_KEY)?,
+		context.get(FILE_KEY)?
+	))
+}
+
+pub fn get_image_request(url: String, context: Option<PageContext>) -> Result<Request> {
+	let page = context
+		.as_ref()
+		.and_then(|context| Some((context, context.get(CHAPTER_KEY)?, page_path(context)?)));
+	let url = match page {
+		Some((context, chapter_id, path)) => {
+			// pages opened long after the page list was loaded need a new base url
+			let expired = context
+				.get(FETCHED_AT_KEY)
+				.and_then(|fetched_at| fetched_at.parse::<i64>().ok())
+				.is_some_and(|fetched_at| current_date() - fetched_at >= SERVER_TTL);
+			if expired {
+				match get_base_url(chapter_id) {
+					Ok(base_url) => format!("{base_url}/{path}"),
+					Err(_) => format!("{UPLOADS_URL}/{path}"),
+				}
+			} else {
+				// skip the original server once it was replaced after a failed page
+				match get_cached_server(chapter_id).filter(|server| server.refetched) {
+					Some(server) => format!("{}/{path}", server.base_url),
+					None => url,
+				}
+			}
+		}
+		None => url,
+	};
+	Ok(Request::get(url)?.header(REQUESTED_AT_HEADER, &current_date().to_string()))
+}
+
+pub fn process_page_image(
+	response: ImageResponse,
+	context: Option<PageContext>,
+) -> Result<ImageRef> {
+	// only failures are reported, since load times can only be measured in whole seconds
+	// and successful loads would be reported as taking no time
+	if (200..300).contains(&response.code) {
+		return Ok(response.image);
+	}
+	let url = response.request.url.as_deref();
+	if let Some(url) = url {
+		let duration = header(&response.request.headers, REQUESTED_AT_HEADER)
+			.and_then(|value| value.parse::<i64>().ok())
+			.map(|requested_at| (current_date() - requested_at) * 1000)
+			.unwrap_or(0);
+		report_failure(url, duration);
+	}
+
+	let Some(context) = context else {
+		return Ok(response.image);
+	};
+	let (Some(chapter_id), Some(path)) = (context.get(CHAPTER_KEY), page_path(&context)) else {
+		return Ok(response.image);
+	};
+
+	// retry with another at-home server, then with the main image server
+	if let Some(base_url) = get_retry_base_url(chapter_id, url) {
+		if let Some(data) = load_image(&format!("{base_url}/{path}")) {
+			return Ok(ImageRef::new(&data));
+		}
+	}
+	load_image(&format!("{UPLOADS_URL}/{path}"))
+		.map(|data| ImageRef::new(&data))
+		.ok_or(AidokuError::message("Failed to load page image"))
+}
+
+// get a header value, ignoring the case of its name
+fn header<'a>(
+	headers: impl IntoIterator<Item = (&'a String, &'a String)>,
+	name: &str,
+) -> Option<&'a str> {
+	headers
+		.into_iter()
+		.find(|(key, _)| key.eq_ignore_ascii_case(name))
+		.map(|(_, value)| value.as_str())
+}
+
+// load an image, reporting a failure if it was served by a mangadex@home node
+fn load_image(url: &str) -> Option<Vec<u8>> {
+	let start = current_date();
+	let data = Request::get(url)
+		.ok()?
+		.send()
+		.ok()
+		.filter(|response| (200..300).contains(&response.status_code()))
+		.and_then(|response| response.get_data().ok());
+	if data.is_none() {
+		report_failure(url, (current_date() - start) * 1000);
+	}
+	data
+}
+
+fn report_failure(url: &str, duration: i64) {
+	// only mangadex@home nodes are reported, not mangadex's own servers
+	if url.starts_with(UPLOADS_URL) || url.contains(".mangadex.org/") {
+		return;
+	}
+	let Ok(body) = serde_json::to_string(&Report {
+		url,
+		success: false,
+		cached: false,
+		bytes: 0,
+		duration,
+	}) else {
+		return;
+	};
+	if let Ok(request) = Request::post(REPORT_URL) {
+		let _ = request
+			.header("Content-Type", "application/json")
+			.body(body)
+			.send();
+	}
+}
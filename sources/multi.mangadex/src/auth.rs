@@ -1,7 +1,9 @@
-use crate::models::TokenResponse;
+use crate::models::{TokenErrorResponse, TokenResponse};
+use crate::net::{self, DexRequest};
 use crate::settings;
 use aidoku::{
 	alloc::String,
+	helpers::uri::encode_uri_component,
 	imports::{
 		error::AidokuError,
 		net::{Request, Response},
@@ -10,39 +12,39 @@ use aidoku::{
 	Result,
 };
 
-const CLIENT_ID: &str = "neko"; // we hijack neko's login system
-const AUTH_URL: &str = "https://auth.mangadex.org";
-const REDIRECT_URI: &str = "neko://mangadex-auth";
+const TOKEN_URL: &str = "https://auth.mangadex.org/realms/mangadex/protocol/openid-connect/token";
 
-fn refresh_access_token() -> Result<TokenResponse> {
-	let Ok(token_response) = settings::get_token() else {
-		settings::clear_token();
-		return Err(AidokuError::Message("Not logged in".into()));
-	};
-
-	let Some(refresh_token) = token_response.refresh_token else {
-		settings::clear_token();
-		return Err(AidokuError::Message("Missing refresh token".into()));
-	};
-
-	let url = format!("{AUTH_URL}/realms/mangadex/protocol/openid-connect/token");
-	let code_verifier = settings::get_code_verifier().unwrap_or_default();
-	let body = format!(
-		"client_id={CLIENT_ID}\
-			&grant_type=refresh_token\
-			&refresh_token={refresh_token}\
-			&code_verifier={code_verifier}\
-			&redirect_uri={REDIRECT_URI}",
-	);
-	let token_response = Request::post(url)?
+// request a token from mangadex's auth server and store it, or none if the credentials
+// were rejected
+fn request_token(body: String) -> Result<Option<TokenResponse>> {
+	let mut response = Request::post(TOKEN_URL)?
 		.header("Content-Type", "application/x-www-form-urlencoded")
 		.body(body)
-		.data()?;
+		.send()?;
+
+	// the auth server responds with oauth errors instead of api errors
+	let status = response.status_code();
+	if status != 429 && !(200..300).contains(&status) {
+		let error = response.get_json::<TokenErrorResponse>().ok();
+		if error
+			.as_ref()
+			.is_some_and(|error| error.error == "invalid_grant")
+		{
+			return Ok(None);
+		}
+		return Err(AidokuError::Message(
+			error
+				.and_then(|error| error.error_description)
+				.filter(|description| !description.is_empty())
+				.unwrap_or_else(|| format!("MangaDex returned status {status}")),
+		));
+	}
+	let token_response = net::check_response(Ok(response))?.get_data()?;
 
 	settings::clear_token();
 
 	let Ok(string_value) = String::from_utf8(token_response) else {
-		return Err(AidokuError::Message("Failed to refresh token".into()));
+		return Err(AidokuError::Message("Failed to parse token".into()));
 	};
 
 	let token_response = serde_json::from_str::<TokenResponse>(&string_value)
@@ -50,11 +52,68 @@ fn refresh_access_token() -> Result<TokenResponse> {
 
 	settings::set_token(&string_value);
 
-	Ok(token_response)
+	Ok(Some(token_response))
+}
+
+// log in with a personal api client using the password grant
+// (https://api.mangadex.org/docs/02-authentication/personal-clients/)
+pub fn login(username: &str, password: &str) -> Result<bool> {
+	let (client_id, client_secret) = settings::get_client()?;
+	let body = format!(
+		"grant_type=password\
+			&username={}\
+			&password={}\
+			&client_id={}\
+			&client_secret={}",
+		encode_uri_component(username),
+		encode_uri_component(password),
+		encode_uri_component(&client_id),
+		encode_uri_component(&client_secret),
+	);
+	// wrong credentials fail the login instead of showing an error
+	Ok(request_token(body)?.is_some_and(|token_response| token_response.access_token.is_some()))
+}
+
+pub fn logout() {
+	settings::clear_token();
+}
+
+fn refresh_access_token() -> Result<TokenResponse> {
+	let Ok(token_response) = settings::get_token() else {
+		settings::clear_token();
+		return Err(AidokuError::Message("Not logged in".into()));
+	};
+
+	let Some(refresh_token) = token_response.refresh_token else {
+		settings::clear_token();
+		return Err(AidokuError::Message("Missing refresh token".into()));
+	};
+
+	let Ok((client_id, client_secret)) = settings::get_client() else {
+		settings::clear_token();
+		return Err(AidokuError::Message("Missing API client".into()));
+	};
+
+	let body = format!(
+		"grant_type=refresh_token\
+			&refresh_token={}\
+			&client_id={}\
+			&client_secret={}",
+		encode_uri_component(&refresh_token),
+		encode_uri_component(&client_id),
+		encode_uri_component(&client_secret),
+	);
+	request_token(body)?.ok_or_else(|| {
+		// the refresh token expired or was revoked
+		settings::clear_token();
+		AidokuError::Message("Session expired, log in again".into())
+	})
 }
 
 pub trait AuthedRequest {
 	fn authed_send(self) -> Result<Response>;
+	// send with authorization if logged in, otherwise send normally
+	fn optionally_authed_send(self) -> Result<Response>;
 }
 
 impl AuthedRequest for Request {
@@ -82,6 +141,14 @@ impl AuthedRequest for Request {
 				.send()?;
 		}
 
-		Ok(response)
+		net::check_response(Ok(response))
+	}
+
+	fn optionally_authed_send(self) -> Result<Response> {
+		if settings::is_logged_in() {
+			self.authed_send()
+		} else {
+			self.dex_send()
+		}
 	}
 }
@@ -0,0 +1,78 @@
+use crate::models::CachedChapter;
+use aidoku::alloc::{String, Vec};
+use core::cmp::Ordering;
+use hashbrown::{HashMap, HashSet};
+
+// hide unavailable chapters when another upload of the same number and language can be read
+pub fn hide_replaced_chapters(chapters: Vec<CachedChapter>) -> Vec<CachedChapter> {
+	let available = chapters
+		.iter()
+		.filter(|chapter| !chapter.unavailable)
+		.filter_map(|chapter| Some((chapter.chapter_number?.to_bits(), chapter.language.clone())))
+		.collect::<HashSet<_>>();
+	chapters
+		.into_iter()
+		.filter(|chapter| {
+			!chapter.unavailable
+				|| chapter.chapter_number.is_none_or(|number| {
+					!available.contains(&(number.to_bits(), chapter.language.clone()))
+				})
+		})
+		.collect()
+}
+
+// the position of a chapter's best group in the preferred groups list
+pub fn group_priority(chapter: &CachedChapter, preferred_groups: &[String]) -> usize {
+	chapter
+		.group_ids
+		.iter()
+		.filter_map(|id| preferred_groups.iter().position(|group| group == id))
+		.min()
+		.unwrap_or(usize::MAX)
+}
+
+// keep one upload per chapter number and language, preferring available uploads, then the
+// earliest group in the preferred groups list, then the newest upload
+pub fn collapse_chapters(
+	chapters: Vec<CachedChapter>,
+	preferred_groups: &[String],
+) -> Vec<CachedChapter> {
+	let priority = |chapter: &CachedChapter| {
+		(
+			chapter.unavailable,
+			group_priority(chapter, preferred_groups),
+		)
+	};
+
+	let mut result: Vec<CachedChapter> = Vec::new();
+	let mut indices = HashMap::new();
+	for chapter in chapters {
+		// chapters without a number (e.g. oneshots) can't be duplicates
+		let Some(number) = chapter.chapter_number else {
+			result.push(chapter);
+			continue;
+		};
+		let key = (number.to_bits(), chapter.language.clone());
+		match indices.get(&key) {
+			Some(&idx) => {
+				let current: &CachedChapter = &result[idx];
+				let is_better = match priority(&chapter).cmp(&priority(current)) {
+					Ordering::Less => true,
+					Ordering::Greater => false,
+					Ordering::Equal => chapter.date_uploaded > current.date_uploaded,
+				};
+				if is_better {
+					result[idx] = chapter;
+				}
+			}
+			None => {
+				indices.insert(key, result.len());
+				result.push(chapter);
+			}
+		}
+	}
+	result
+}
+
+#[cfg(test)]
+mod test;
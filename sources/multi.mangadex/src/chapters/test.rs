@@ -0,0 +1,134 @@
+#![expect(clippy::unwrap_used)]
+
+use super::*;
+use aidoku::alloc::vec;
+use aidoku_test::aidoku_test;
+
+fn chapter(key: &str, number: f32, language: &str, group: &str, uploaded: i64) -> CachedChapter {
+	CachedChapter {
+		key: key.into(),
+		chapter_number: Some(number),
+		date_uploaded: Some(uploaded),
+		language: Some(language.into()),
+		group_ids: vec![group.into()],
+		..Default::default()
+	}
+}
+
+fn keys(chapters: &[CachedChapter]) -> Vec<&str> {
+	chapters
+		.iter()
+		.map(|chapter| chapter.key.as_str())
+		.collect()
+}
+
+#[aidoku_test]
+fn group_priority_order() {
+	let preferred_groups = vec![String::from("b"), String::from("a")];
+	assert_eq!(
+		group_priority(&chapter("1", 1.0, "en", "b", 0), &preferred_groups),
+		0
+	);
+	assert_eq!(
+		group_priority(&chapter("2", 1.0, "en", "a", 0), &preferred_groups),
+		1
+	);
+	assert_eq!(
+		group_priority(&chapter("3", 1.0, "en", "c", 0), &preferred_groups),
+		usize::MAX
+	);
+
+	// joint uploads use their best group
+	let mut joint = chapter("4", 1.0, "en", "c", 0);
+	joint.group_ids.push("a".into());
+	assert_eq!(group_priority(&joint, &preferred_groups), 1);
+}
+
+#[aidoku_test]
+fn collapse_chapters_preferred_group() {
+	let preferred_groups = vec![String::from("b"), String::from("a")];
+	let chapters = collapse_chapters(
+		vec![
+			chapter("a1", 1.0, "en", "a", 300),
+			chapter("b1", 1.0, "en", "b", 100),
+			chapter("c1", 1.0, "en", "c", 200),
+			chapter("a2", 2.0, "en", "a", 400),
+			chapter("c2", 2.0, "en", "c", 500),
+		],
+		&preferred_groups,
+	);
+	assert_eq!(keys(&chapters), ["b1", "a2"]);
+}
+
+#[aidoku_test]
+fn collapse_chapters_newest_fallback() {
+	let chapters = collapse_chapters(
+		vec![
+			chapter("old", 1.0, "en", "a", 100),
+			chapter("new", 1.0, "en", "b", 300),
+			chapter("mid", 1.0, "en", "c", 200),
+		],
+		&[],
+	);
+	assert_eq!(keys(&chapters), ["new"]);
+}
+
+#[aidoku_test]
+fn collapse_chapters_per_language() {
+	let chapters = collapse_chapters(
+		vec![
+			chapter("en1", 1.0, "en", "a", 100),
+			chapter("es1", 1.0, "es", "b", 100),
+			chapter("en1b", 1.0, "en", "c", 200),
+		],
+		&[],
+	);
+	assert_eq!(keys(&chapters), ["en1b", "es1"]);
+}
+
+#[aidoku_test]
+fn collapse_chapters_prefers_available() {
+	let preferred_groups = vec![String::from("a")];
+	let mut unavailable = chapter("a1", 1.0, "en", "a", 300);
+	unavailable.unavailable = true;
+	let chapters = collapse_chapters(
+		vec![unavailable, chapter("b1", 1.0, "en", "b", 100)],
+		&preferred_groups,
+	);
+	assert_eq!(keys(&chapters), ["b1"]);
+}
+
+#[aidoku_test]
+fn collapse_chapters_keeps_unnumbered() {
+	let oneshot = CachedChapter {
+		key: "oneshot".into(),
+		..Default::default()
+	};
+	let chapters = collapse_chapters(
+		vec![
+			oneshot.clone(),
+			CachedChapter {
+				key: "extra".into(),
+				..oneshot
+			},
+		],
+		&[],
+	);
+	assert_eq!(keys(&chapters), ["oneshot", "extra"]);
+}
+
+#[aidoku_test]
+fn hide_replaced_chapters_per_language() {
+	let mut removed_en = chapter("removed-en", 1.0, "en", "a", 100);
+	removed_en.unavailable = true;
+	let mut removed_es = chapter("removed-es", 1.0, "es", "a", 100);
+	removed_es.unavailable = true;
+	let chapters = hide_replaced_chapters(vec![
+		removed_en,
+		chapter("en", 1.0, "en", "b", 200),
+		removed_es,
+	]);
+	// the spanish chapter has no other upload, so it's still shown as locked
+	assert_eq!(keys(&chapters), ["en", "removed-es"]);
+	assert!(chapters.last().unwrap().unavailable);
+}
@@ -1,6 +1,9 @@
 use crate::MangaDex;
-use crate::{models::*, settings};
-use crate::{API_URL, CUSTOM_LISTS};
+use crate::{
+	created_since, API_URL, CUSTOM_LISTS, CUSTOM_LIST_PREFIX, FOLLOWS_FEED_LISTING, HOME_LIST_SIZE,
+	MONTH,
+};
+use crate::{models::*, net, net::DexRequest, settings};
 use aidoku::imports::net::Response;
 use aidoku::Link;
 use aidoku::{
@@ -8,54 +11,28 @@ use aidoku::{
 	imports::{
 		error::AidokuError,
 		net::{Request, RequestError},
-		std::{current_date, send_partial_result},
+		std::send_partial_result,
 	},
 	prelude::*,
-	Home, HomeComponent, HomeLayout, HomePartialResult, Listing, ListingKind, Manga,
-	MangaWithChapter, Result,
+	Home, HomeComponent, HomeComponentValue, HomeLayout, HomePartialResult, Listing, ListingKind,
+	Manga, MangaWithChapter, Result,
 };
-use chrono::{TimeZone, Utc};
 use hashbrown::HashSet;
 
+type SendResult = core::result::Result<Response, RequestError>;
+
+struct CustomList {
+	id: String,
+	name: String,
+	entries: Result<Vec<String>>,
+}
+
 impl Home for MangaDex {
 	fn get_home(&self) -> Result<HomeLayout> {
 		// fetch custom list titles and manga ids
-		struct CustomList<'a> {
-			id: &'a str,
-			name: String,
-			entries: Vec<&'a str>,
-		}
-		let mut custom_list_requests = Request::send_all(
-			CUSTOM_LISTS
-				.iter()
-				.map(|list| format!("{API_URL}/list/{list}"))
-				.map(|url| Request::get(url).expect("invalid url format")),
-		);
-		let custom_lists = &mut custom_list_requests
-			.iter_mut()
-			.filter_map(|req| {
-				req.as_mut()
-					.ok()?
-					.get_json::<DexResponse<DexCustomList>>()
-					.ok()
-			})
-			.map(|res| CustomList {
-				id: res.data.id,
-				name: res.data.attributes.name,
-				entries: res
-					.data
-					.relationships
-					.iter()
-					.filter_map(|relationship| {
-						if relationship.r#type == "manga" {
-							Some(relationship.id)
-						} else {
-							None
-						}
-					})
-					.collect(),
-			})
-			.collect::<Vec<_>>();
+		let custom_lists = get_custom_lists();
+
+		let logged_in = settings::is_logged_in();
 
 		// send basic home layout
 		{
@@ -63,33 +40,93 @@ impl Home for MangaDex {
 				HomeComponent {
 					title: Some("Popular New Titles".into()),
 					subtitle: None,
-					value: aidoku::HomeComponentValue::empty_big_scroller(),
+					value: HomeComponentValue::empty_big_scroller(),
 				},
 				HomeComponent {
 					title: Some("Latest Updates".into()),
 					subtitle: None,
-					value: aidoku::HomeComponentValue::empty_manga_chapter_list(),
+					value: HomeComponentValue::empty_manga_chapter_list(),
 				},
 			];
+			if logged_in {
+				components.push(HomeComponent {
+					title: Some("Follows Feed".into()),
+					subtitle: None,
+					value: HomeComponentValue::empty_manga_chapter_list(),
+				});
+			}
 			for CustomList { name, .. } in custom_lists.iter() {
 				components.push(HomeComponent {
 					title: Some(name.clone()),
 					subtitle: None,
-					value: aidoku::HomeComponentValue::empty_scroller(),
+					value: HomeComponentValue::empty_scroller(),
 				});
 			}
 			components.push(HomeComponent {
 				title: Some("Recently Added".into()),
 				subtitle: None,
-				value: aidoku::HomeComponentValue::empty_scroller(),
+				value: HomeComponentValue::empty_scroller(),
 			});
 			send_partial_result(&HomePartialResult::Layout(HomeLayout { components }));
 		}
 
-		let languages = settings::get_languages_with_key("translatedLanguage")?;
-		let content_ratings = settings::get_content_ratings()?;
+		// followed manga chapters list
+		if logged_in {
+			send_component(
+				"Follows Feed",
+				self.get_follows_feed(1, 15).map(|(mut entries, _)| {
+					entries.truncate(6);
+					HomeComponentValue::MangaChapterList {
+						page_size: None,
+						entries,
+						listing: Some(Listing {
+							id: String::from(FOLLOWS_FEED_LISTING),
+							name: String::from("Follows Feed"),
+							kind: ListingKind::Default,
+						}),
+					}
+				}),
+				HomeComponentValue::empty_manga_chapter_list(),
+			);
+		}
+
+		// a missing filter default only fails the components that are filtered by it
+		let filters = settings::get_languages_with_key("translatedLanguage")
+			.and_then(|languages| Ok((languages, settings::get_content_ratings()?)));
+		let (languages, content_ratings) = match filters {
+			Ok(filters) => filters,
+			Err(error) => {
+				let message = match error {
+					AidokuError::Message(message) => message,
+					_ => String::from("Unable to fetch filter settings"),
+				};
+				let failed = || Err(AidokuError::Message(message.clone()));
+				send_component(
+					"Popular New Titles",
+					failed(),
+					HomeComponentValue::empty_big_scroller(),
+				);
+				send_component(
+					"Latest Updates",
+					failed(),
+					HomeComponentValue::empty_manga_chapter_list(),
+				);
+				for list in custom_lists {
+					send_component(&list.name, failed(), HomeComponentValue::empty_scroller());
+				}
+				send_component(
+					"Recently Added",
+					failed(),
+					HomeComponentValue::empty_scroller(),
+				);
+				return Ok(HomeLayout::default());
+			}
+		};
+		let blocked_tags = settings::get_blocked_tags();
 
-		let responses: [core::result::Result<Response, RequestError>; 3] = Request::send_all([
+		let created_since = created_since(MONTH);
+
+		let requests = [
 			// popular
 			Request::get(format!(
 				"{API_URL}/manga\
@@ -98,21 +135,19 @@ impl Home for MangaDex {
 					&includes[]=author\
 					&order[followedCount]=desc\
 					&hasAvailableChapters=true\
-					&createdAtSince={}\
-					{content_ratings}",
-				// gmt time, one month ago
-				Utc.timestamp_opt(current_date() as i64 - 2630000, 0)
-					.unwrap()
-					.format("%Y-%m-%dT%H:%M:%S")
-			))?,
+					{created_since}\
+					{content_ratings}\
+					{blocked_tags}"
+			)),
 			// recently added
 			Request::get(format!(
 				"{API_URL}/manga\
 					?limit=15\
 					&order[createdAt]=desc\
 					&includes[]=cover_art\
-					{content_ratings}"
-			))?,
+					{content_ratings}\
+					{blocked_tags}"
+			)),
 			// latest
 			Request::get(format!(
 				"{API_URL}/chapter\
@@ -121,181 +156,268 @@ impl Home for MangaDex {
 					&order[readableAt]=desc\
 					{languages}\
 					{content_ratings}"
-			))?,
-		])
-		.try_into()
-		.expect("requests vec length should be 3");
+			)),
+		]
+		.into_iter()
+		.collect::<core::result::Result<Vec<_>, _>>()?;
+		let mut responses = Request::send_all(requests).into_iter();
+		let (popular_res, recent_res, chapters_res) =
+			(responses.next(), responses.next(), responses.next());
 
-		let [popular_res, recent_res, chapters_res] = responses;
+		send_component(
+			"Popular New Titles",
+			parse_popular(popular_res),
+			HomeComponentValue::empty_big_scroller(),
+		);
 
-		// popular scroller
+		send_component(
+			"Latest Updates",
+			parse_latest(chapters_res, &content_ratings, &blocked_tags),
+			HomeComponentValue::empty_manga_chapter_list(),
+		);
+
+		// custom lists components
 		{
-			let popular_manga = popular_res?
-				.get_json::<DexResponse<Vec<DexManga>>>()
-				.map_err(|_| AidokuError::message("Failed to parse popular manga"))?
-				.data
+			// lists without entries would match every manga, so they aren't requested
+			let requests = custom_lists
 				.iter()
-				.map(|value| Manga {
-					key: String::from(value.id),
-					title: value.title().unwrap_or_default(),
-					cover: value.cover(),
-					description: value.description(),
-					tags: Some(value.tags()),
-					content_rating: value.content_rating(),
-					..Default::default()
+				.filter_map(|list| {
+					let entries = list.entries.as_ref().ok().filter(|e| !e.is_empty())?;
+					// lists can hold more titles than the api accepts as ids at once
+					Request::get(format!(
+						"{API_URL}/manga\
+							?limit={HOME_LIST_SIZE}\
+							&includes[]=cover_art\
+							{content_ratings}\
+							{blocked_tags}\
+							&ids[]={}",
+						entries[..entries.len().min(HOME_LIST_SIZE)].join("&ids[]=")
+					))
+					.ok()
 				})
-				.collect::<Vec<Manga>>();
-			send_partial_result(&HomePartialResult::Component(HomeComponent {
-				title: Some(String::from("Popular New Titles")),
-				subtitle: None,
-				value: aidoku::HomeComponentValue::BigScroller {
-					entries: popular_manga,
-					auto_scroll_interval: Some(10.0),
-				},
-			}));
-		}
-
-		// recently added scroller
-		{
-			let added_manga = recent_res?
-				.get_json::<DexResponse<Vec<DexManga>>>()
-				.map_err(|_| AidokuError::message("Failed to parse recent manga"))?
-				.data
-				.into_iter()
-				.map(|value| value.into_basic_manga().into())
-				.collect::<Vec<Link>>();
+				.collect::<Vec<_>>();
+			let mut responses = Request::send_all(requests).into_iter();
 
-			send_partial_result(&HomePartialResult::Component(HomeComponent {
-				title: Some(String::from("Recently Added")),
-				subtitle: None,
-				value: aidoku::HomeComponentValue::Scroller {
-					entries: added_manga,
-					listing: Some(Listing {
-						id: String::from("recent"),
-						name: String::from("Recently Added"),
-						kind: ListingKind::Default,
+			for list in custom_lists {
+				let entries = list.entries.and_then(|entries| {
+					if entries.is_empty() {
+						Ok(Vec::new())
+					} else {
+						parse_manga_links(responses.next())
+					}
+				});
+				send_component(
+					&list.name,
+					entries.map(|entries| HomeComponentValue::Scroller {
+						entries,
+						listing: Some(Listing {
+							id: format!("{CUSTOM_LIST_PREFIX}{}", list.id),
+							name: list.name.clone(),
+							kind: ListingKind::Default,
+						}),
 					}),
-				},
-			}));
+					HomeComponentValue::empty_scroller(),
+				);
+			}
 		}
 
-		// latest chapters list
-		{
-			let chapters_data = chapters_res?
-				.get_data()
-				.map_err(|_| AidokuError::message("Failed to fetch latest chapters"))?;
-			// get one chapter per unique manga
-			let mut seen = HashSet::new();
-			let chapters: Vec<DexChapter> =
-				serde_json::from_slice::<DexResponse<Vec<DexChapter>>>(&chapters_data)
-					.map_err(|_| AidokuError::JsonParseError)?
-					.data
-					.into_iter()
-					.filter(|chapter| {
-						chapter
-							.manga_id()
-							.map(|id| seen.insert(id))
-							.unwrap_or(false)
-					})
-					.take(6)
-					.collect();
+		send_component(
+			"Recently Added",
+			parse_manga_links(recent_res).map(|entries| HomeComponentValue::Scroller {
+				entries,
+				listing: Some(Listing {
+					id: String::from("recent"),
+					name: String::from("Recently Added"),
+					kind: ListingKind::Default,
+				}),
+			}),
+			HomeComponentValue::empty_scroller(),
+		);
 
-			let manga_ids = chapters
-				.iter()
-				.filter_map(|value| value.manga_id().map(|m| format!("&ids[]={m}")))
-				.collect::<String>();
+		Ok(HomeLayout::default())
+	}
+}
 
-			let latest_manga_url = format!(
-				"{API_URL}/manga\
-					?includes[]=cover_art\
-					{content_ratings}\
-					{manga_ids}"
-			);
-			let latest_manga = Request::get(latest_manga_url)?
-				.send()?
-				.get_json::<DexResponse<Vec<DexManga>>>()?
-				.data
-				.into_iter()
-				.map(|value| value.into_basic_manga())
-				.collect::<Vec<Manga>>();
+// send a loaded component, or an empty placeholder with the error if it failed to load
+fn send_component(title: &str, value: Result<HomeComponentValue>, placeholder: HomeComponentValue) {
+	let (subtitle, value) = match value {
+		Ok(value) => (None, value),
+		Err(error) => (
+			Some(match error {
+				AidokuError::Message(message) => format!("Failed to load: {message}"),
+				_ => String::from("Failed to load"),
+			}),
+			placeholder,
+		),
+	};
+	send_partial_result(&HomePartialResult::Component(HomeComponent {
+		title: Some(String::from(title)),
+		subtitle,
+		value,
+	}));
+}
 
-			let latest_chapters = chapters
+// fetch the custom lists shown on the home page, keeping failed lists so they show an error
+fn get_custom_lists() -> Vec<CustomList> {
+	// user lists are named by their id until their title is known
+	let lists = CUSTOM_LISTS
+		.iter()
+		.map(|(id, name)| (String::from(*id), String::from(*name)))
+		.chain(
+			settings::get_home_lists()
 				.into_iter()
-				.map(|value| MangaWithChapter {
-					manga: latest_manga
-						.iter()
-						.find(|m| m.key == value.manga_id().expect("need manga"))
-						.expect("need manga!")
-						.clone(),
-					chapter: value.into(),
-				})
-				.collect::<Vec<MangaWithChapter>>();
+				.map(|id| (id.clone(), id)),
+		)
+		.collect::<Vec<_>>();
 
-			send_partial_result(&HomePartialResult::Component(HomeComponent {
-				title: Some(String::from("Latest Updates")),
-				subtitle: None,
-				value: aidoku::HomeComponentValue::MangaChapterList {
-					page_size: None,
-					entries: latest_chapters,
-					listing: Some(Listing {
-						id: String::from("latest"),
-						name: String::from("Latest Updates"),
-						kind: ListingKind::Default,
-					}),
-				},
-			}));
+	let mut requests = Vec::new();
+	let mut requested = Vec::new();
+	for (id, _) in lists.iter() {
+		match Request::get(format!("{API_URL}/list/{id}")) {
+			Ok(request) => {
+				requests.push(request);
+				requested.push(true);
+			}
+			Err(_) => requested.push(false),
 		}
+	}
+	let mut responses = Request::send_all(requests).into_iter();
 
-		// custom lists components
-		{
-			let custom_list_responses = Request::send_all(custom_lists.iter().map(|list| {
-				Request::get(format!(
-					"{API_URL}/manga\
-						?limit=32\
-						&includes[]=cover_art\
-						{content_ratings}\
-						&ids[]={}",
-					list.entries.join("&ids[]=")
-				))
-				.unwrap()
-			}));
-			let custom_lists = custom_lists
-				.iter_mut()
-				.zip(custom_list_responses)
-				.filter_map(|(list, res)| {
-					Some((
-						list.id,
-						list.name.clone(),
-						res.ok()?
-							.get_json::<DexResponse<Vec<DexManga>>>()
-							.map(|response| {
-								response
-									.data
-									.into_iter()
-									.map(|value| value.into_basic_manga().into())
-									.collect::<Vec<Link>>()
-							})
-							.ok()?,
-					))
-				})
-				.collect::<Vec<(&str, String, Vec<Link>)>>();
+	lists
+		.into_iter()
+		.zip(requested)
+		.map(|((id, name), requested)| {
+			let list = if requested {
+				responses
+					.next()
+					.ok_or(AidokuError::message("Missing response"))
+					.and_then(parse_custom_list)
+			} else {
+				Err(AidokuError::message("Invalid list id"))
+			};
+			list.unwrap_or_else(|error| CustomList {
+				id,
+				name,
+				entries: Err(error),
+			})
+		})
+		.collect()
+}
 
-			for (id, name, entries) in custom_lists {
-				send_partial_result(&HomePartialResult::Component(HomeComponent {
-					title: Some(name.clone()),
-					subtitle: None,
-					value: aidoku::HomeComponentValue::Scroller {
-						entries,
-						listing: Some(Listing {
-							id: format!("list-{id}"),
-							name,
-							kind: ListingKind::Default,
-						}),
-					},
-				}));
-			}
-		}
+fn parse_custom_list(response: SendResult) -> Result<CustomList> {
+	let list = net::check_response(response)?
+		.get_json::<DexResponse<DexCustomList>>()
+		.map_err(|_| AidokuError::message("Failed to parse list"))?
+		.data;
+	Ok(CustomList {
+		id: String::from(list.id),
+		name: list.attributes.name,
+		entries: Ok(list
+			.relationships
+			.iter()
+			.filter(|relationship| relationship.r#type == "manga")
+			.map(|relationship| String::from(relationship.id))
+			.collect()),
+	})
+}
 
-		Ok(HomeLayout::default())
-	}
+fn parse_popular(response: Option<SendResult>) -> Result<HomeComponentValue> {
+	let mut response =
+		net::check_response(response.ok_or(AidokuError::message("Missing response"))?)?;
+	let entries = response
+		.get_json::<DexResponse<Vec<DexManga>>>()
+		.map_err(|_| AidokuError::message("Failed to parse popular manga"))?
+		.data
+		.iter()
+		.map(|value| Manga {
+			key: String::from(value.id),
+			title: value.title().unwrap_or_default(),
+			cover: value.cover(),
+			description: value.description(),
+			tags: Some(value.tags()),
+			content_rating: value.content_rating(),
+			..Default::default()
+		})
+		.collect::<Vec<Manga>>();
+	Ok(HomeComponentValue::BigScroller {
+		entries,
+		auto_scroll_interval: Some(10.0),
+	})
+}
+
+fn parse_manga_links(response: Option<SendResult>) -> Result<Vec<Link>> {
+	let mut response =
+		net::check_response(response.ok_or(AidokuError::message("Missing response"))?)?;
+	Ok(response
+		.get_json::<DexResponse<Vec<DexManga>>>()
+		.map_err(|_| AidokuError::message("Failed to parse manga"))?
+		.data
+		.into_iter()
+		.map(|value| value.into_basic_manga().into())
+		.collect())
+}
+
+fn parse_latest(
+	response: Option<SendResult>,
+	content_ratings: &str,
+	blocked_tags: &str,
+) -> Result<HomeComponentValue> {
+	let chapters_data =
+		net::check_response(response.ok_or(AidokuError::message("Missing response"))?)?
+			.get_data()
+			.map_err(|_| AidokuError::message("Failed to fetch latest chapters"))?;
+	// get one chapter per unique manga
+	let mut seen = HashSet::new();
+	let chapters: Vec<DexChapter> =
+		serde_json::from_slice::<DexResponse<Vec<DexChapter>>>(&chapters_data)
+			.map_err(|_| AidokuError::JsonParseError)?
+			.data
+			.into_iter()
+			.filter(|chapter| chapter.manga_id().is_some_and(|id| seen.insert(id)))
+			.take(6)
+			.collect();
+
+	let latest_manga = if chapters.is_empty() {
+		Vec::new()
+	} else {
+		let manga_ids = chapters
+			.iter()
+			.filter_map(|value| value.manga_id().map(|m| format!("&ids[]={m}")))
+			.collect::<String>();
+		Request::get(format!(
+			"{API_URL}/manga\
+				?includes[]=cover_art\
+				{content_ratings}\
+				{blocked_tags}\
+				{manga_ids}"
+		))?
+		.dex_send()?
+		.get_json::<DexResponse<Vec<DexManga>>>()?
+		.data
+		.into_iter()
+		.map(|value| value.into_basic_manga())
+		.collect::<Vec<Manga>>()
+	};
+
+	// chapters of manga that were filtered out or are missing are skipped
+	let entries = chapters
+		.into_iter()
+		.filter_map(|value| {
+			let manga_id = value.manga_id()?;
+			Some(MangaWithChapter {
+				manga: latest_manga.iter().find(|m| m.key == manga_id)?.clone(),
+				chapter: value.into(),
+			})
+		})
+		.collect::<Vec<MangaWithChapter>>();
+
+	Ok(HomeComponentValue::MangaChapterList {
+		page_size: None,
+		entries,
+		listing: Some(Listing {
+			id: String::from("latest"),
+			name: String::from("Latest Updates"),
+			kind: ListingKind::Default,
+		}),
+	})
 }
@@ -1,49 +1,108 @@
 #![no_std]
 use aidoku::{
 	alloc::{string::ToString, vec, String, Vec},
-	helpers::uri::QueryParameters,
+	helpers::uri::{encode_uri_component, QueryParameters},
 	imports::{
+		canvas::ImageRef,
 		error::AidokuError,
-		net::{set_rate_limit, Request, TimeUnit},
-		std::send_partial_result,
+		net::{Request, Response},
+		std::{current_date, send_partial_result},
 	},
 	prelude::*,
-	AlternateCoverProvider, Chapter, DeepLinkHandler, DeepLinkResult, DynamicListings, FilterValue,
-	Listing, ListingKind, ListingProvider, Manga, MangaPageResult, Page, PageContent, Result,
-	Source,
+	AlternateCoverProvider, BasicLoginHandler, Chapter, DeepLinkHandler, DeepLinkResult,
+	DynamicFilters, DynamicListings, Filter, FilterValue, ImageRequestProvider, ImageResponse,
+	Listing, ListingKind, ListingProvider, Manga, MangaPageResult, MangaWithChapter,
+	MultiSelectFilter, NotificationHandler, Page, PageContext, PageImageProcessor, Result, Source,
 };
-use core::fmt::Write;
+use chrono::{TimeZone, Utc};
+use core::{cmp::Ordering, fmt::Write};
 use hashbrown::HashSet;
 
+mod at_home;
 mod auth;
+mod chapters;
+mod mangaplus;
 mod models;
+mod net;
 mod settings;
 
 mod home;
 
 use auth::*;
 use models::*;
+use net::DexRequest;
 
 const API_URL: &str = "https://api.mangadex.org";
 const COVER_URL: &str = "https://uploads.mangadex.org";
 
 const PAGE_SIZE: i32 = 20;
+const FEED_PAGE_SIZE: i32 = 500;
+const FEED_MAX_ATTEMPTS: i32 = 3;
+// how often the full chapter feed is refetched instead of only updated chapters
+const FULL_SYNC_INTERVAL: i64 = 60 * 60 * 24;
+// overlap between incremental syncs, to account for clock differences
+const SYNC_MARGIN: i64 = 60 * 5;
+// requests sent together, kept within the per-second rate limit
+const REQUEST_BATCH_SIZE: usize = 5;
+const COVER_PAGE_SIZE: i32 = 100;
+// how long fetched search tags are used before being refetched
+const TAG_CACHE_TTL: i64 = 60 * 60 * 24 * 7;
+// tag groups shown as search filters, in order
+const TAG_GROUPS: &[(&str, &str)] = &[
+	("content", "Content"),
+	("format", "Format"),
+	("genre", "Genre"),
+	("theme", "Theme"),
+];
 const CUSTOM_LIST_PREFIX: &str = "list-";
+// maximum number of lists the api returns in one request
+const USER_LISTS_PAGE_SIZE: i32 = 100;
+// number of titles shown in a home page list
+const HOME_LIST_SIZE: usize = 32;
+// maximum number of authors matched by an author or artist filter
+const AUTHOR_SEARCH_LIMIT: i32 = 20;
+const AUTHOR_LISTING_PREFIX: &str = "author-";
+const GROUP_LISTING_PREFIX: &str = "group-";
+const TAG_LISTING_PREFIX: &str = "tag-";
+// maximum number of ids the api accepts in a manga list request
+const LIBRARY_CHUNK_SIZE: usize = 100;
+const LIBRARY_LISTING_PREFIX: &str = "library-";
+const FOLLOWS_FEED_LISTING: &str = "follows-feed";
+
+// reading statuses with their own library listing
+const LIBRARY_STATUSES: &[(&str, &str)] = &[
+	("reading", "Reading"),
+	("plan_to_read", "Plan to Read"),
+	("completed", "Completed"),
+	("on_hold", "On Hold"),
+	("re_reading", "Re-reading"),
+	("dropped", "Dropped"),
+];
 
 // listings to use on the home page
-const CUSTOM_LISTS: &[&str] = &[
-	"f66ebc10-ef89-46d1-be96-bb704559e04a", // Self-Published
-	"805ba886-dd99-4aa4-b460-4bd7c7b71352", // Staff Picks
-	"5c5e6e39-0b4b-413e-be59-27b1ba03d1b9", // Featured by Supporters
-	"a5ba5473-07b2-4d0a-aefd-90d9d4a04521", // Seasonal
+const CUSTOM_LISTS: &[(&str, &str)] = &[
+	("f66ebc10-ef89-46d1-be96-bb704559e04a", "Self-Published"),
+	("805ba886-dd99-4aa4-b460-4bd7c7b71352", "Staff Picks"),
+	(
+		"5c5e6e39-0b4b-413e-be59-27b1ba03d1b9",
+		"Featured by Supporters",
+	),
+	("a5ba5473-07b2-4d0a-aefd-90d9d4a04521", "Seasonal"),
 ];
 
+// number of titles requested for a page of the random listing
+const RANDOM_PAGE_SIZE: usize = 10;
+// rounds of random requests made to fill a page with titles in the selected languages
+const RANDOM_MAX_ATTEMPTS: i32 = 3;
+// time ranges for the most followed listings, in seconds
+const MONTH: i64 = 2630000;
+const YEAR: i64 = 31557600;
+
 struct MangaDex;
 
 impl Source for MangaDex {
 	fn new() -> Self {
-		// 5 requests per second (https://api.mangadex.org/docs/2-limitations/)
-		set_rate_limit(5, 1, TimeUnit::Seconds);
+		net::set_default_rate_limit();
 		Self
 	}
 
@@ -65,9 +124,24 @@ impl Source for MangaDex {
 			match filter {
 				FilterValue::Text { id, value } => match id.as_str() {
 					"author" | "artist" => {
+						if value.trim().is_empty() {
+							continue;
+						}
 						let ids = self.get_author_ids(&value)?;
-						if let Some(first) = ids.first() {
-							qs.push("authorOrArtist", Some(first));
+						if ids.is_empty() {
+							// no author with the name exists, so nothing can match
+							return Ok(MangaPageResult {
+								entries: Vec::new(),
+								has_next_page: false,
+							});
+						}
+						let key = if id == "author" {
+							"authors[]"
+						} else {
+							"artists[]"
+						};
+						for id in ids {
+							qs.push(key, Some(&id));
 						}
 					}
 					_ => return Err(AidokuError::Message("Invalid text filter id".into())),
@@ -170,11 +244,13 @@ impl Source for MangaDex {
 				?includes%5B%5D=cover_art\
 				&limit={PAGE_SIZE}\
 				&offset={offset}\
-				&{qs}",
+				&{qs}\
+				{}",
+			settings::get_blocked_tags()
 		);
 
 		let (entries, has_next_page) = Request::get(url)?
-			.send()?
+			.dex_send()?
 			.get_json::<DexResponse<Vec<DexManga>>>()
 			.map(|response| {
 				(
@@ -200,19 +276,40 @@ impl Source for MangaDex {
 		needs_chapters: bool,
 	) -> Result<Manga> {
 		if needs_details {
+			let [details_res, stats_res]: [core::result::Result<Response, _>; 2] =
+				Request::send_all([
+					Request::get(format!(
+						"{API_URL}/manga/{}\
+							?includes[]=cover_art\
+							&includes[]=author\
+							&includes[]=artist",
+						manga.key
+					))?,
+					Request::get(format!("{API_URL}/statistics/manga/{}", manga.key))?,
+				])
+				.try_into()
+				.map_err(|_| AidokuError::message("Missing manga details response"))?;
+
 			manga.copy_from(
-				Request::get(format!(
-					"{API_URL}/manga/{}\
-						?includes[]=cover_art\
-						&includes[]=author\
-						&includes[]=artist",
-					manga.key
-				))?
-				.send()?
-				.get_json::<DexResponse<DexManga>>()?
-				.data
-				.into(),
+				net::check_response(details_res)?
+					.get_json::<DexResponse<DexManga>>()?
+					.data
+					.into(),
 			);
+
+			// statistics are best effort, so they're left out if unavailable
+			let summary = net::check_response(stats_res)
+				.ok()
+				.and_then(|mut response| response.get_json::<DexStatisticsResponse>().ok())
+				.and_then(|response| response.statistics.get(&manga.key).cloned())
+				.and_then(|stats| serde_json::from_value::<DexMangaStatistics>(stats).ok())
+				.and_then(|stats| stats.summary());
+			if let Some(summary) = summary {
+				manga.description = Some(match manga.description.take() {
+					Some(description) => format!("{summary}\n\n{description}"),
+					None => summary,
+				});
+			}
 			if needs_chapters {
 				send_partial_result(&manga);
 			}
@@ -226,7 +323,7 @@ impl Source for MangaDex {
 				"{API_URL}/manga/{}/feed\
 					?order[volume]=desc\
 					&order[chapter]=desc\
-					&limit=500\
+					&limit={FEED_PAGE_SIZE}\
 					&contentRating[]=pornographic\
 					&contentRating[]=erotica\
 					&contentRating[]=suggestive\
@@ -238,87 +335,153 @@ impl Source for MangaDex {
 				manga.key
 			);
 
-			let (mut chapters, total) = Request::get(&url)?
-				.send()?
-				.get_json::<DexResponse<Vec<DexChapter>>>()
-				.map(|response| {
-					(
-						response
-							.data
-							.into_iter()
-							.filter(|value| !value.has_external_url())
-							.map(|value| value.into())
-							.collect::<Vec<Chapter>>(),
-						response.total,
-					)
-				})?;
-
-			// fetch chapters in pages of 500
-			if let Some(total) = total {
-				let mut offset = 500;
-				while offset < total {
-					let url = format!("{url}&offset={offset}");
-					if let Ok(response) = Request::get(&url)?
-						.send()?
-						.get_json::<DexResponse<Vec<DexChapter>>>()
-					{
-						chapters.extend(
-							response
-								.data
-								.into_iter()
-								.filter(|value| !value.has_external_url())
-								.map(|value| value.into()),
-						);
+			// only fetch chapters updated since the last sync, unless the settings that
+			// affect the feed have changed or a full resync is due
+			let now = current_date();
+			let feed_settings = format!(
+				"{languages}{blocked_groups}{}",
+				settings::get_mangaplus_chapters()
+			);
+			let cache = settings::get_feed_cache(&manga.key).filter(|(synced_at, cache)| {
+				cache.settings == feed_settings && now - synced_at < FULL_SYNC_INTERVAL
+			});
+
+			let cache = if let Some((synced_at, cache)) = cache {
+				let since = Utc
+					.timestamp_opt(synced_at - SYNC_MARGIN, 0)
+					.single()
+					.map(|date| date.format("%Y-%m-%dT%H:%M:%S").to_string())
+					.unwrap_or_default();
+				// chapters with a delayed publish date can be updated before the last sync
+				// but published after it, so both kinds of changes are fetched. the feed combines
+				// its filters, so they can't be sent as a single request
+				let mut updated =
+					self.get_chapter_feed(&format!("{url}&updatedAtSince={since}"))?;
+				for chapter in self.get_chapter_feed(&format!("{url}&publishAtSince={since}"))? {
+					if !updated.iter().any(|c| c.key == chapter.key) {
+						updated.push(chapter);
 					}
-					offset += 500;
 				}
-			}
 
-			manga.chapters = Some(chapters);
+				// the stored cache is only rewritten when the feed changed
+				if updated.is_empty() {
+					cache
+				} else {
+					let mut chapters = cache
+						.chapters
+						.into_iter()
+						.filter(|chapter| !updated.iter().any(|c| c.key == chapter.key))
+						.collect::<Vec<_>>();
+					chapters.extend(updated);
+					chapters.sort_by(|a, b| {
+						// match the feed order (volume then chapter, descending, missing first)
+						let volume_a = a.volume_number.unwrap_or(f32::MAX);
+						let volume_b = b.volume_number.unwrap_or(f32::MAX);
+						let chapter_a = a.chapter_number.unwrap_or(f32::MAX);
+						let chapter_b = b.chapter_number.unwrap_or(f32::MAX);
+						volume_b
+							.partial_cmp(&volume_a)
+							.unwrap_or(Ordering::Equal)
+							.then(chapter_b.partial_cmp(&chapter_a).unwrap_or(Ordering::Equal))
+					});
+					let cache = FeedCache { chapters, ..cache };
+					settings::set_feed_cache(&manga.key, &cache);
+					cache
+				}
+			} else {
+				let cache = FeedCache {
+					settings: feed_settings,
+					chapters: self.get_chapter_feed(&url)?,
+					// thumbnails are best effort, so missing covers shouldn't fail the update
+					covers: self
+						.get_covers(&manga.key, "")
+						.unwrap_or_default()
+						.into_iter()
+						.filter(|cover| cover.volume_number().is_some())
+						.collect(),
+				};
+				settings::set_feed_cache(&manga.key, &cache);
+				cache
+			};
+			settings::set_feed_synced(&manga.key, now);
+
+			let chapters = if settings::get_collapse_chapters() {
+				chapters::collapse_chapters(cache.chapters, &settings::get_preferred_groups())
+			} else {
+				chapters::hide_replaced_chapters(cache.chapters)
+			};
+			manga.chapters = Some(
+				chapters
+					.into_iter()
+					.map(|chapter| {
+						let mut chapter = Chapter::from(chapter);
+						chapter.thumbnail = volume_cover(&cache.covers, &chapter, &manga.key);
+						chapter
+					})
+					.collect(),
+			);
 		}
 
 		Ok(manga)
 	}
 
-	fn get_page_list(&self, _manga: Manga, chapter: Chapter) -> Result<Vec<Page>> {
-		let force_port = settings::get_force_port();
-		let url = format!(
-			"{API_URL}/at-home/server/{}{}",
-			chapter.key,
-			if force_port { "?forcePort443=true" } else { "" }
-		);
+	fn get_page_list(&self, manga: Manga, chapter: Chapter) -> Result<Vec<Page>> {
+		if let Some(chapter_id) = chapter.url.as_deref().and_then(mangaplus::chapter_id) {
+			return mangaplus::get_page_list(chapter_id);
+		}
 
-		Request::get(&url)?
-			.send()?
-			.get_json::<DexAtHomeResponse>()
-			.and_then(|response| {
-				let data_saver = settings::get_data_saver();
-				let base_url = format!(
-					"{}/{}/{}",
-					response.base_url,
-					if data_saver { "dataSaver" } else { "data" },
-					response.chapter.hash
-				);
+		// only unavailable chapters fall back to another upload, and other errors are returned
+		// so a failed request doesn't open another group's translation
+		if !chapter.locked {
+			if let Some(pages) = at_home::get_page_list(&chapter.key)? {
+				return Ok(pages);
+			}
+		}
 
-				let chapter_data = if data_saver {
-					response.chapter.data_saver
-				} else {
-					response.chapter.data
-				};
-				chapter_data
-					.map(|data| {
-						data.iter()
-							.map(|value| Page {
-								content: PageContent::url(format!("{base_url}/{value}")),
-								..Default::default()
-							})
-							.collect::<Vec<Page>>()
-					})
-					.ok_or(AidokuError::message("Missing chapter data"))
-			})
+		// the chapter is locked or its pages were removed, so read another group's upload
+		let key = find_replacement(&manga.key, &chapter).ok_or(AidokuError::message(
+			"This chapter is unavailable and has no other uploads",
+		))?;
+		at_home::get_page_list(&key)?.ok_or(AidokuError::message("This chapter is unavailable"))
 	}
 }
 
+// query parameter for manga added within a number of seconds, in gmt time
+fn created_since(seconds: i64) -> String {
+	Utc.timestamp_opt(current_date() - seconds, 0)
+		.single()
+		.map(|date| format!("&createdAtSince={}", date.format("%Y-%m-%dT%H:%M:%S")))
+		.unwrap_or_default()
+}
+
+// get the cover of a chapter's volume, preferring one in the chapter's language
+fn volume_cover(covers: &[DexCoverArt], chapter: &Chapter, manga_id: &str) -> Option<String> {
+	let volume = chapter.volume_number?;
+	covers
+		.iter()
+		.filter(|cover| cover.volume_number() == Some(volume))
+		.min_by_key(|cover| chapter.language.as_deref() != Some(cover.attributes.locale.as_str()))
+		.map(|cover| cover.url(manga_id))
+}
+
+// find another group's readable upload of a chapter in a manga's cached feed
+fn find_replacement(manga_id: &str, chapter: &Chapter) -> Option<String> {
+	let number = chapter.chapter_number?;
+	let preferred_groups = settings::get_preferred_groups();
+	settings::get_feed_cache(manga_id)?
+		.1
+		.chapters
+		.into_iter()
+		.filter(|c| {
+			c.key != chapter.key
+				&& !c.unavailable
+				&& c.chapter_number == Some(number)
+				&& c.language == chapter.language
+		})
+		.min_by_key(|c| chapters::group_priority(c, &preferred_groups))
+		.map(|c| c.key)
+}
+
 impl ListingProvider for MangaDex {
 	fn get_manga_list(&self, listing: Listing, page: i32) -> Result<MangaPageResult> {
 		match listing.id.as_str() {
@@ -341,36 +504,201 @@ impl ListingProvider for MangaDex {
 				}],
 			),
 			"latest" => self.get_latest_manga(page),
-			"library" => self.get_library(page),
+			"random" => self.get_random_manga(),
+			"rating" => self.get_ranked_manga("rating", None, page),
+			// the api has no follow counts per period, so these rank titles created in the period
+			// by their all-time follows
+			"follows-month" => self.get_ranked_manga("followedCount", Some(MONTH), page),
+			"follows-year" => self.get_ranked_manga("followedCount", Some(YEAR), page),
+			"library" => self.get_library(page, None),
+			FOLLOWS_FEED_LISTING => {
+				self.get_follows_feed(page, PAGE_SIZE)
+					.map(|(entries, has_next_page)| MangaPageResult {
+						entries: entries.into_iter().map(|entry| entry.manga).collect(),
+						has_next_page,
+					})
+			}
+			_ if listing.id.starts_with(LIBRARY_LISTING_PREFIX) => {
+				self.get_library(page, Some(&listing.id[LIBRARY_LISTING_PREFIX.len()..]))
+			}
 			_ if listing.id.starts_with(CUSTOM_LIST_PREFIX) => {
-				self.get_mangadex_list(&listing.id[CUSTOM_LIST_PREFIX.len()..])
+				self.get_mangadex_list(&listing.id[CUSTOM_LIST_PREFIX.len()..], page)
 			}
+			_ if listing.id.starts_with(AUTHOR_LISTING_PREFIX) => self.get_related_manga(
+				&format!(
+					"authorOrArtist={}",
+					&listing.id[AUTHOR_LISTING_PREFIX.len()..]
+				),
+				page,
+			),
+			_ if listing.id.starts_with(GROUP_LISTING_PREFIX) => self.get_related_manga(
+				&format!("group={}", &listing.id[GROUP_LISTING_PREFIX.len()..]),
+				page,
+			),
+			_ if listing.id.starts_with(TAG_LISTING_PREFIX) => self.get_related_manga(
+				&format!("includedTags[]={}", &listing.id[TAG_LISTING_PREFIX.len()..]),
+				page,
+			),
 			_ => Err(AidokuError::Unimplemented),
 		}
 	}
 }
 
 impl MangaDex {
-	// get a list of author ids from a name query
-	fn get_author_ids(&self, name: &str) -> Result<Vec<String>> {
-		let url = format!("{API_URL}/author?name={name}",);
+	// get every cover of a manga, optionally filtered by locale query parameters
+	fn get_covers(&self, manga_id: &str, locales: &str) -> Result<Vec<DexCoverArt>> {
+		let url = format!(
+			"{API_URL}/cover?manga[]={manga_id}{locales}&order[volume]=asc&limit={COVER_PAGE_SIZE}"
+		);
+		let (mut covers, total) = Request::get(&url)?
+			.dex_send()?
+			.get_json::<DexResponse<Vec<DexCoverArt>>>()
+			.map(|response| (response.data, response.total))?;
 
-		let ids = Request::get(url)?
-			.send()?
-			.get_json::<DexResponse<Vec<DexData>>>()?
+		if let Some(total) = total {
+			let mut offset = COVER_PAGE_SIZE;
+			while offset < total {
+				let response = Request::get(format!("{url}&offset={offset}"))?
+					.dex_send()?
+					.get_json::<DexResponse<Vec<DexCoverArt>>>()?;
+				covers.extend(response.data);
+				offset += COVER_PAGE_SIZE;
+			}
+		}
+
+		Ok(covers)
+	}
+
+	// get the search tags, refetching them once the cache expires
+	fn get_tags(&self) -> Vec<CachedTag> {
+		let now = current_date();
+		let cache = settings::get_tag_cache();
+		match cache {
+			Some(cache) if now - cache.fetched_at < TAG_CACHE_TTL => cache.tags,
+			_ => match self.fetch_tags() {
+				Ok(tags) => {
+					settings::set_tag_cache(&TagCache {
+						fetched_at: now,
+						tags: tags.clone(),
+					});
+					tags
+				}
+				// use an outdated cache or the bundled tags when offline
+				Err(_) => cache.map(|cache| cache.tags).unwrap_or_else(|| {
+					serde_json::from_str(include_str!("tags.json")).unwrap_or_default()
+				}),
+			},
+		}
+	}
+
+	fn fetch_tags(&self) -> Result<Vec<CachedTag>> {
+		Ok(Request::get(format!("{API_URL}/manga/tag"))?
+			.dex_send()?
+			.get_json::<DexResponse<Vec<DexTag>>>()?
 			.data
-			.iter()
-			.map(|value| value.id.to_string())
-			.collect::<Vec<String>>();
+			.into_iter()
+			.map(CachedTag::from)
+			.collect())
+	}
+
+	// get every chapter in a manga feed, fetching the pages after the first concurrently
+	fn get_chapter_feed(&self, url: &str) -> Result<Vec<CachedChapter>> {
+		let mangaplus_chapters = settings::get_mangaplus_chapters();
+		let parse_feed_page = |response: &mut Response| {
+			response
+				.get_json::<DexResponse<Vec<DexChapter>>>()
+				.ok()
+				.map(|response| {
+					(
+						response
+							.data
+							.into_iter()
+							.filter(|value| {
+								!value.has_external_url()
+									|| (mangaplus_chapters && value.is_mangaplus())
+							})
+							.map(|value| value.into())
+							.collect::<Vec<CachedChapter>>(),
+						response.total,
+					)
+				})
+		};
+
+		let (mut chapters, total) = parse_feed_page(&mut Request::get(url)?.dex_send()?)
+			.ok_or(AidokuError::message("Failed to parse chapter feed"))?;
+
+		let Some(total) = total else {
+			return Ok(chapters);
+		};
+
+		// the remaining pages are sent together, and any that fail are retried
+		let offsets = (FEED_PAGE_SIZE..total)
+			.step_by(FEED_PAGE_SIZE as usize)
+			.collect::<Vec<_>>();
+		let mut pages: Vec<Option<Vec<CachedChapter>>> = offsets.iter().map(|_| None).collect();
+		let mut attempts = 0;
+		while pages.iter().any(|page| page.is_none()) {
+			if attempts >= FEED_MAX_ATTEMPTS {
+				return Err(AidokuError::message("Failed to fetch chapter feed"));
+			}
+			attempts += 1;
+
+			let missing = pages
+				.iter()
+				.enumerate()
+				.filter(|(_, page)| page.is_none())
+				.map(|(idx, _)| idx)
+				.collect::<Vec<_>>();
+			for batch in missing.chunks(REQUEST_BATCH_SIZE) {
+				let requests = batch
+					.iter()
+					.map(|idx| Request::get(format!("{url}&offset={}", offsets[*idx])))
+					.collect::<core::result::Result<Vec<_>, _>>()?;
+				let responses = Request::send_all(requests);
+				for (idx, response) in batch.iter().zip(responses) {
+					pages[*idx] = net::check_response(response)
+						.ok()
+						.and_then(|mut response| parse_feed_page(&mut response))
+						.map(|(chapters, _)| chapters);
+				}
+			}
+		}
+
+		chapters.extend(pages.into_iter().flatten().flatten());
+		Ok(chapters)
+	}
 
-		Ok(ids)
+	// get the ids of the authors matching a name, only keeping exact matches if there are any
+	// since the ids are combined with or
+	fn get_author_ids(&self, name: &str) -> Result<Vec<String>> {
+		let url = format!(
+			"{API_URL}/author?limit={AUTHOR_SEARCH_LIMIT}&name={}",
+			encode_uri_component(name)
+		);
+
+		let authors = Request::get(url)?
+			.dex_send()?
+			.get_json::<DexResponse<Vec<DexNamedEntity>>>()?
+			.data;
+
+		let name = name.trim().to_lowercase();
+		let (exact, partial): (Vec<_>, Vec<_>) = authors
+			.into_iter()
+			.partition(|author| author.attributes.name.trim().to_lowercase() == name);
+
+		Ok(if exact.is_empty() { partial } else { exact }
+			.into_iter()
+			.map(|author| author.id)
+			.collect())
 	}
 
-	// get a custom list
-	fn get_mangadex_list(&self, id: &str) -> Result<MangaPageResult> {
+	// get a page of a custom list
+	fn get_mangadex_list(&self, id: &str, page: i32) -> Result<MangaPageResult> {
 		let content_ratings = settings::get_content_ratings()?;
 
-		let mut list_res = Request::get(format!("{API_URL}/list/{id}"))?.send()?;
+		// private lists are only visible to their owner
+		let mut list_res =
+			Request::get(format!("{API_URL}/list/{id}"))?.optionally_authed_send()?;
 
 		let manga_ids = list_res
 			.get_json::<DexResponse<DexCustomList>>()?
@@ -386,16 +714,31 @@ impl MangaDex {
 			})
 			.collect::<Vec<&str>>();
 
-		// assume the list is 32 items or less (mangadex site uses this value)
-		let entries = Request::get(format!(
+		let offset = ((page - 1) * PAGE_SIZE) as usize;
+		let page_ids = manga_ids
+			.iter()
+			.skip(offset)
+			.take(PAGE_SIZE as usize)
+			.copied()
+			.collect::<Vec<&str>>();
+		if page_ids.is_empty() {
+			return Ok(MangaPageResult {
+				entries: Vec::new(),
+				has_next_page: false,
+			});
+		}
+
+		let mut entries = Request::get(format!(
 			"{API_URL}/manga\
-					?limit=32\
+					?limit={PAGE_SIZE}\
 					&includes[]=cover_art\
 					{content_ratings}\
+					{}\
 					&ids[]={}",
-			manga_ids.join("&ids[]=")
+			settings::get_blocked_tags(),
+			page_ids.join("&ids[]=")
 		))?
-		.send()?
+		.dex_send()?
 		.get_json::<DexResponse<Vec<DexManga>>>()
 		.map(|response| {
 			response
@@ -404,13 +747,167 @@ impl MangaDex {
 				.map(|value| value.into_basic_manga())
 				.collect::<Vec<Manga>>()
 		})?;
+		// keep the list's order
+		entries.sort_by_key(|manga| page_ids.iter().position(|id| *id == manga.key));
 
 		Ok(MangaPageResult {
 			entries,
-			has_next_page: false,
+			has_next_page: offset + (PAGE_SIZE as usize) < manga_ids.len(),
 		})
 	}
 
+	// get the most followed manga matching a query parameter, such as an author or tag
+	fn get_related_manga(&self, param: &str, page: i32) -> Result<MangaPageResult> {
+		self.get_manga_page(&format!("{param}&order[followedCount]=desc"), page)
+	}
+
+	// get the highest ranked manga with chapters in the selected languages,
+	// optionally only those added within a number of seconds
+	fn get_ranked_manga(
+		&self,
+		order: &str,
+		since: Option<i64>,
+		page: i32,
+	) -> Result<MangaPageResult> {
+		let languages = settings::get_languages_with_key("availableTranslatedLanguage")?;
+		let created_since = since.map(created_since).unwrap_or_default();
+		self.get_manga_page(
+			&format!("hasAvailableChapters=true&order[{order}]=desc{languages}{created_since}"),
+			page,
+		)
+	}
+
+	// get a page of manga matching query parameters
+	fn get_manga_page(&self, params: &str, page: i32) -> Result<MangaPageResult> {
+		let content_ratings = settings::get_content_ratings()?;
+		let offset = (page - 1) * PAGE_SIZE;
+
+		let response = Request::get(format!(
+			"{API_URL}/manga\
+				?{params}\
+				&includes[]=cover_art\
+				&limit={PAGE_SIZE}\
+				&offset={offset}\
+				{content_ratings}\
+				{}",
+			settings::get_blocked_tags()
+		))?
+		.dex_send()?
+		.get_json::<DexResponse<Vec<DexManga>>>()?;
+
+		Ok(MangaPageResult {
+			has_next_page: response.total.is_some_and(|t| offset + PAGE_SIZE < t),
+			entries: response
+				.data
+				.into_iter()
+				.map(|value| value.into_basic_manga())
+				.collect(),
+		})
+	}
+
+	// get random manga with chapters in the selected languages
+	fn get_random_manga(&self) -> Result<MangaPageResult> {
+		let content_ratings = settings::get_content_ratings()?;
+		let blocked_tags = settings::get_blocked_tags();
+		let languages = settings::get_languages()?;
+
+		// the random endpoint returns a single title, so a page is made of several requests
+		let request = || {
+			Request::get(format!(
+				"{API_URL}/manga/random\
+					?includes[]=cover_art\
+					{content_ratings}\
+					{blocked_tags}"
+			))
+		};
+
+		let mut error = None;
+		let mut seen = HashSet::new();
+		let mut entries = Vec::new();
+		// the endpoint can't filter by language, so requests are repeated until the page is
+		// filled with titles that have chapters in the selected languages
+		for _ in 0..RANDOM_MAX_ATTEMPTS {
+			if entries.len() >= RANDOM_PAGE_SIZE {
+				break;
+			}
+			let requests = (entries.len()..RANDOM_PAGE_SIZE)
+				.map(|_| request())
+				.collect::<core::result::Result<Vec<_>, _>>()?;
+			entries.extend(
+				Request::send_all(requests)
+					.into_iter()
+					.filter_map(|response| {
+						let mut response = net::check_response(response)
+							.map_err(|err| error = Some(err))
+							.ok()?;
+						let manga = response.get_json::<DexResponse<DexManga>>().ok()?.data;
+						if !languages.is_empty()
+							&& !manga
+								.attributes
+								.available_translated_languages
+								.iter()
+								.any(|lang| languages.contains(lang))
+						{
+							return None;
+						}
+						Some(manga.into_basic_manga())
+					})
+					.filter(|manga| seen.insert(manga.key.clone())),
+			);
+		}
+
+		if let (true, Some(error)) = (entries.is_empty(), error) {
+			return Err(error);
+		}
+
+		// random pages never run out, unless no titles could be found in the selected languages
+		Ok(MangaPageResult {
+			has_next_page: !entries.is_empty(),
+			entries,
+		})
+	}
+
+	// get the name of an author, scanlation group, or custom list for a listing
+	fn get_entity_name(&self, path: &str, id: &str) -> Option<String> {
+		Request::get(format!("{API_URL}/{path}/{id}"))
+			.ok()?
+			.dex_send()
+			.ok()?
+			.get_json::<DexResponse<DexNamedEntity>>()
+			.ok()
+			.map(|response| response.data.attributes.name)
+	}
+
+	// get the logged in user's own and followed custom lists
+	fn get_user_lists(&self) -> Result<Vec<Listing>> {
+		let mut listings: Vec<Listing> = Vec::new();
+		for path in ["user/list", "user/follows/list"] {
+			let mut offset = 0;
+			loop {
+				let mut response = Request::get(format!(
+					"{API_URL}/{path}?limit={USER_LISTS_PAGE_SIZE}&offset={offset}"
+				))?
+				.authed_send()?;
+				let lists = response.get_json::<DexResponse<Vec<DexCustomList>>>()?;
+				for list in lists.data {
+					let id = format!("{CUSTOM_LIST_PREFIX}{}", list.id);
+					if !listings.iter().any(|listing| listing.id == id) {
+						listings.push(Listing {
+							id,
+							name: list.attributes.name,
+							kind: ListingKind::Default,
+						});
+					}
+				}
+				offset += USER_LISTS_PAGE_SIZE;
+				if !lists.total.is_some_and(|total| offset < total) {
+					break;
+				}
+			}
+		}
+		Ok(listings)
+	}
+
 	// get the manga associated with the latest uploaded chapters
 	fn get_latest_manga(&self, page: i32) -> Result<MangaPageResult> {
 		let languages = settings::get_languages_with_key("translatedLanguage")?;
@@ -427,7 +924,7 @@ impl MangaDex {
 				{content_ratings}\
 				{languages}"
 		))?
-		.send()?; // get_data instead of json so that we can use it as a reference
+		.dex_send()?; // get_data instead of json so that we can use it as a reference
 
 		// get unique manga ids for the chapters
 		let mut seen = HashSet::new();
@@ -441,6 +938,7 @@ impl MangaDex {
 
 		let has_next_page = !manga_ids.is_empty();
 
+		let blocked_tags = settings::get_blocked_tags();
 		let ids_params = manga_ids.iter().fold(String::new(), |mut output, id| {
 			let _ = write!(output, "&ids[]={id}");
 			output
@@ -450,10 +948,11 @@ impl MangaDex {
 			"{API_URL}/manga\
 				?includes[]=cover_art\
 				{content_ratings}\
+				{blocked_tags}\
 				{ids_params}"
 		);
 		let entries = Request::get(url)?
-			.send()?
+			.dex_send()?
 			.get_json::<DexResponse<Vec<DexManga>>>()?
 			.data
 			.into_iter()
@@ -466,65 +965,243 @@ impl MangaDex {
 		})
 	}
 
-	// get the logged in user's library
-	fn get_library(&self, page: i32) -> Result<MangaPageResult> {
-		let status_ids = Request::get(format!("{API_URL}/manga/status"))?
-			.authed_send()?
-			.get_json::<DexStatusResponse>()?
-			.statuses
-			.keys()
+	// get the latest uploaded chapters of the logged in user's followed manga,
+	// with one chapter per manga
+	fn get_follows_feed(&self, page: i32, limit: i32) -> Result<(Vec<MangaWithChapter>, bool)> {
+		let languages = settings::get_languages_with_key("translatedLanguage")?;
+
+		let offset = (page - 1) * limit;
+
+		let mut chapters_res = Request::get(format!(
+			"{API_URL}/user/follows/manga/feed\
+				?includes[]=scanlation_group\
+				&includes[]=user\
+				&limit={limit}\
+				&offset={offset}\
+				&order[readableAt]=desc\
+				&contentRating[]=safe\
+				&contentRating[]=suggestive\
+				&contentRating[]=erotica\
+				&contentRating[]=pornographic\
+				{languages}"
+		))?
+		.authed_send()?;
+		let response = chapters_res.get_json::<DexResponse<Vec<DexChapter>>>()?;
+
+		let has_next_page = response.total.is_some_and(|t| offset + limit < t);
+
+		// get one chapter per unique manga
+		let mut seen = HashSet::new();
+		let chapters = response
+			.data
+			.into_iter()
+			.filter(|chapter| chapter.manga_id().is_some_and(|id| seen.insert(id)))
+			.collect::<Vec<_>>();
+		if chapters.is_empty() {
+			return Ok((Vec::new(), has_next_page));
+		}
+
+		let ids_params = chapters
+			.iter()
+			.filter_map(|chapter| chapter.manga_id())
 			.fold(String::new(), |mut output, id| {
 				let _ = write!(output, "&ids[]={id}");
 				output
 			});
-
-		let offset = (page - 1) * PAGE_SIZE;
-
-		let manga_url = format!(
-			"{API_URL}/manga?\
-				&limit={PAGE_SIZE}\
-				&offset={offset}\
+		let manga = Request::get(format!(
+			"{API_URL}/manga\
+				?limit={limit}\
 				&includes[]=cover_art\
 				&contentRating[]=safe\
 				&contentRating[]=suggestive\
 				&contentRating[]=erotica\
 				&contentRating[]=pornographic\
-				{status_ids}",
-		);
+				{}\
+				{ids_params}",
+			settings::get_blocked_tags()
+		))?
+		.dex_send()?
+		.get_json::<DexResponse<Vec<DexManga>>>()?
+		.data
+		.into_iter()
+		.map(|value| value.into_basic_manga())
+		.collect::<Vec<Manga>>();
+
+		// chapters of manga with blocked tags are dropped along with their manga
+		let entries = chapters
+			.into_iter()
+			.filter_map(|chapter| {
+				let manga_id = chapter.manga_id()?;
+				let manga = manga.iter().find(|m| m.key == manga_id)?.clone();
+				Some(MangaWithChapter {
+					manga,
+					chapter: chapter.into(),
+				})
+			})
+			.collect();
 
-		let (entries, has_next_page) = Request::get(manga_url)?
-			.authed_send()?
-			.get_json::<DexResponse<Vec<DexManga>>>()
-			.map(|response| {
-				(
-					response
-						.data
-						.into_iter()
-						.map(|value| value.into_basic_manga())
-						.collect::<Vec<Manga>>(),
-					response.total.is_some_and(|t| offset + PAGE_SIZE < t),
-				)
-			})?;
+		Ok((entries, has_next_page))
+	}
+
+	// get the logged in user's library, optionally only with the given reading status
+	fn get_library(&self, page: i32, status: Option<&str>) -> Result<MangaPageResult> {
+		let cache_key = status.unwrap_or("all");
+
+		// the library is fetched in full on the first page, and later pages are read from the cache
+		let library = match settings::get_library_cache(cache_key).filter(|_| page > 1) {
+			Some(library) => library,
+			None => {
+				let library = self.fetch_library(status)?;
+				settings::set_library_cache(cache_key, &library);
+				library
+			}
+		};
+
+		let offset = ((page - 1) * PAGE_SIZE) as usize;
+		let entries = library
+			.iter()
+			.skip(offset)
+			.take(PAGE_SIZE as usize)
+			.cloned()
+			.map(Manga::from)
+			.collect();
 
 		Ok(MangaPageResult {
 			entries,
-			has_next_page,
+			has_next_page: offset + (PAGE_SIZE as usize) < library.len(),
 		})
 	}
+
+	// fetch every manga in the logged in user's library, sorted by the library sort setting
+	fn fetch_library(&self, status: Option<&str>) -> Result<Vec<CachedManga>> {
+		let mut status_res = Request::get(format!(
+			"{API_URL}/manga/status{}",
+			status.map(|s| format!("?status={s}")).unwrap_or_default()
+		))?
+		.authed_send()?;
+		let status_ids = status_res
+			.get_json::<DexStatusResponse>()?
+			.statuses
+			.into_iter()
+			.map(|(id, _)| id)
+			.collect::<Vec<String>>();
+
+		// blocked tags aren't excluded here, since the user chose to add these titles
+		// request manga details in chunks of ids that the api accepts
+		let mut library = Vec::new();
+		for batch in status_ids
+			.chunks(LIBRARY_CHUNK_SIZE)
+			.collect::<Vec<_>>()
+			.chunks(REQUEST_BATCH_SIZE)
+		{
+			let requests = batch
+				.iter()
+				.map(|ids| {
+					Request::get(format!(
+						"{API_URL}/manga\
+							?limit={LIBRARY_CHUNK_SIZE}\
+							&includes[]=cover_art\
+							&contentRating[]=safe\
+							&contentRating[]=suggestive\
+							&contentRating[]=erotica\
+							&contentRating[]=pornographic\
+							&ids[]={}",
+						ids.join("&ids[]=")
+					))
+				})
+				.collect::<core::result::Result<Vec<_>, _>>()?;
+			for response in Request::send_all(requests) {
+				let mut response = net::check_response(response)?;
+				library.extend(
+					response
+						.get_json::<DexResponse<Vec<DexManga>>>()?
+						.data
+						.into_iter()
+						.map(CachedManga::from),
+				);
+			}
+		}
+
+		if settings::get_library_sort() == "updated" {
+			library.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+		} else {
+			library.sort_by_cached_key(|manga| manga.title.to_lowercase());
+		}
+
+		Ok(library)
+	}
 }
 
-// show the library listing if we're logged in
+// show the home page lists, and the library listings if we're logged in
 impl DynamicListings for MangaDex {
 	fn get_dynamic_listings(&self) -> Result<Vec<Listing>> {
+		let mut listings = CUSTOM_LISTS
+			.iter()
+			.map(|(id, name)| Listing {
+				id: format!("{CUSTOM_LIST_PREFIX}{id}"),
+				name: String::from(*name),
+				kind: ListingKind::Default,
+			})
+			.collect::<Vec<_>>();
 		if settings::is_logged_in() {
-			Ok(Vec::from([Listing {
-				id: String::from("library"),
-				name: String::from("Library"),
+			listings.extend([
+				Listing {
+					id: String::from("library"),
+					name: String::from("Library"),
+					kind: ListingKind::Default,
+				},
+				Listing {
+					id: String::from(FOLLOWS_FEED_LISTING),
+					name: String::from("Follows Feed"),
+					kind: ListingKind::Default,
+				},
+			]);
+			listings.extend(LIBRARY_STATUSES.iter().map(|(status, name)| Listing {
+				id: format!("{LIBRARY_LISTING_PREFIX}{status}"),
+				name: String::from(*name),
 				kind: ListingKind::Default,
-			}]))
-		} else {
-			Ok(Vec::new())
+			}));
+			// lists failing to load shouldn't hide the other listings
+			listings.extend(self.get_user_lists().unwrap_or_default());
 		}
+		Ok(listings)
+	}
+}
+
+impl DynamicFilters for MangaDex {
+	fn get_dynamic_filters(&self) -> Result<Vec<Filter>> {
+		let tags = self.get_tags();
+
+		let mut languages = settings::get_dex_languages();
+		languages.push(String::from("en"));
+
+		Ok(TAG_GROUPS
+			.iter()
+			.filter_map(|(group, title)| {
+				let mut options = tags
+					.iter()
+					.filter(|tag| tag.group == *group)
+					.filter_map(|tag| Some((tag.name.get_preferred(&languages)?, tag.id.clone())))
+					.collect::<Vec<_>>();
+				if options.is_empty() {
+					return None;
+				}
+				options.sort_by_cached_key(|(name, _)| name.to_lowercase());
+				let (options, ids): (Vec<_>, Vec<_>) = options.into_iter().unzip();
+				Some(
+					MultiSelectFilter {
+						id: (*group).into(),
+						title: Some((*title).into()),
+						is_genre: true,
+						can_exclude: true,
+						options: options.into_iter().map(Into::into).collect(),
+						ids: Some(ids.into_iter().map(Into::into).collect()),
+						..Default::default()
+					}
+					.into(),
+				)
+			})
+			.collect())
 	}
 }
 
@@ -532,48 +1209,50 @@ impl AlternateCoverProvider for MangaDex {
 	fn get_alternate_covers(&self, manga: Manga) -> Result<Vec<String>> {
 		let locales = settings::get_languages_with_key("locales")?;
 
-		let url = format!(
-			"{API_URL}/cover?manga[]={}{}{}&limit=100",
-			manga.key,
-			locales,
-			if !locales.contains("locales[]=ja") {
-				"&locales[]=ja"
-			} else {
-				""
-			}
-		);
-		let (mut items, total) = Request::get(&url)?
-			.send()?
-			.get_json::<DexResponse<Vec<DexCoverArt>>>()
-			.map(|response| (response.data, response.total))?;
+		let locales = if !locales.contains("locales[]=ja") {
+			format!("{locales}&locales[]=ja")
+		} else {
+			locales
+		};
+		let mut covers = self.get_covers(&manga.key, &locales)?;
+
+		// order by volume, with covers in the preferred languages first
+		let mut languages = settings::get_dex_languages();
+		languages.push(String::from("ja"));
+		covers.sort_by(|a, b| {
+			let volume_a = a.volume_number().unwrap_or(f32::MAX);
+			let volume_b = b.volume_number().unwrap_or(f32::MAX);
+			let locale_rank = |cover: &DexCoverArt| {
+				languages
+					.iter()
+					.position(|lang| *lang == cover.attributes.locale)
+					.unwrap_or(languages.len())
+			};
+			volume_a
+				.partial_cmp(&volume_b)
+				.unwrap_or(Ordering::Equal)
+				.then(locale_rank(a).cmp(&locale_rank(b)))
+		});
 
-		if let Some(total) = total {
-			let mut offset = 100;
-			while offset < total {
-				let url = format!("{url}&offset={offset}");
-				if let Ok(response) = Request::get(url)?
-					.send()?
-					.get_json::<DexResponse<Vec<DexCoverArt>>>()
-				{
-					items.extend(response.data);
-				}
-				offset += 100;
-			}
-		}
+		Ok(covers.iter().map(|cover| cover.url(&manga.key)).collect())
+	}
+}
 
-		let result = items
-			.iter()
-			.map(|item| {
-				format!(
-					"{COVER_URL}/covers/{}/{}{}",
-					manga.key,
-					item.attributes.file_name,
-					settings::get_cover_quality()
-				)
-			})
-			.collect::<Vec<String>>();
+impl BasicLoginHandler for MangaDex {
+	fn handle_basic_login(&self, key: String, username: String, password: String) -> Result<bool> {
+		if key != "login" {
+			return Ok(false);
+		}
+		auth::login(&username, &password)
+	}
+}
 
-		Ok(result)
+impl NotificationHandler for MangaDex {
+	fn handle_notification(&self, notification: String) {
+		// the stored token is removed when logging out
+		if notification == "login" && !settings::has_login_credentials() {
+			auth::logout();
+		}
 	}
 }
 
@@ -589,6 +1268,14 @@ impl DeepLinkHandler for MangaDex {
 
 		const TITLE_PATH: &str = "title/";
 		const CHAPTER_PATH: &str = "chapter/";
+		const AUTHOR_PATH: &str = "author/";
+		const GROUP_PATH: &str = "group/";
+		const TAG_PATH: &str = "tag/";
+		const LIST_PATH: &str = "list/";
+
+		// get the id from the start of a path, ignoring the trailing slug
+		let path_id =
+			|path: &str| String::from(&path[..path.find(['/', '?']).unwrap_or(path.len())]);
 
 		if let Some(key) = url.strip_prefix(TITLE_PATH) {
 			// ex: https://mangadex.org/title/a96676e5-8ae2-425e-b549-7f15dd34a6d8/komi-san-wa-komyushou-desu
@@ -604,7 +1291,7 @@ impl DeepLinkHandler for MangaDex {
 			let chapter_key = &key[..end];
 
 			let url = format!("{API_URL}/chapter/{chapter_key}");
-			let mut res = Request::get(&url)?.send()?;
+			let mut res = Request::get(&url)?.dex_send()?;
 
 			let manga_key = res
 				.get_json::<DexResponse<DexChapter>>()?
@@ -616,17 +1303,98 @@ impl DeepLinkHandler for MangaDex {
 				manga_key: manga_key.into(),
 				key: chapter_key.into(),
 			}))
+		} else if let Some(key) = url.strip_prefix(AUTHOR_PATH) {
+			// ex: https://mangadex.org/author/b6045b0c-2ab5-4b27-8e7e-1b16d0a2a4bc/oda-eiichiro
+			let id = path_id(key);
+			Ok(Some(DeepLinkResult::Listing(Listing {
+				name: self
+					.get_entity_name("author", &id)
+					.unwrap_or_else(|| String::from("Author")),
+				id: format!("{AUTHOR_LISTING_PREFIX}{id}"),
+				kind: ListingKind::Default,
+			})))
+		} else if let Some(key) = url.strip_prefix(GROUP_PATH) {
+			// ex: https://mangadex.org/group/4f1de6a2-f0c5-4ac5-bce5-02c7dbb67deb/mangaplus
+			let id = path_id(key);
+			Ok(Some(DeepLinkResult::Listing(Listing {
+				name: self
+					.get_entity_name("group", &id)
+					.unwrap_or_else(|| String::from("Group")),
+				id: format!("{GROUP_LISTING_PREFIX}{id}"),
+				kind: ListingKind::Default,
+			})))
+		} else if let Some(key) = url.strip_prefix(TAG_PATH) {
+			// ex: https://mangadex.org/tag/391b0423-d847-456f-aff0-8b0cfc03066b/action
+			let id = path_id(key);
+			let mut languages = settings::get_dex_languages();
+			languages.push(String::from("en"));
+			Ok(Some(DeepLinkResult::Listing(Listing {
+				name: self
+					.get_tags()
+					.into_iter()
+					.find(|tag| tag.id == id)
+					.and_then(|tag| tag.name.get_preferred(&languages))
+					.unwrap_or_else(|| String::from("Tag")),
+				id: format!("{TAG_LISTING_PREFIX}{id}"),
+				kind: ListingKind::Default,
+			})))
+		} else if let Some(key) = url.strip_prefix(LIST_PATH) {
+			// ex: https://mangadex.org/list/805ba886-dd99-4aa4-b460-4bd7c7b71352/staff-picks
+			let id = path_id(key);
+			Ok(Some(DeepLinkResult::Listing(Listing {
+				name: self
+					.get_entity_name("list", &id)
+					.unwrap_or_else(|| String::from("List")),
+				id: format!("{CUSTOM_LIST_PREFIX}{id}"),
+				kind: ListingKind::Default,
+			})))
 		} else {
 			Ok(None)
 		}
 	}
 }
 
+impl ImageRequestProvider for MangaDex {
+	fn get_image_request(&self, url: String, context: Option<PageContext>) -> Result<Request> {
+		at_home::get_image_request(url, context)
+	}
+}
+
+impl PageImageProcessor for MangaDex {
+	fn process_page_image(
+		&self,
+		response: ImageResponse,
+		context: Option<PageContext>,
+	) -> Result<ImageRef> {
+		// mangaplus images are encrypted, so the raw data needs to be decrypted. the response
+		// only holds the decoded image, which encrypted data can't produce, so the image is
+		// downloaded again to get its bytes
+		let Some(key) = context
+			.as_ref()
+			.and_then(|context| context.get(mangaplus::ENCRYPTION_KEY))
+		else {
+			return at_home::process_page_image(response, context);
+		};
+		let url = response
+			.request
+			.url
+			.ok_or(AidokuError::message("Missing image url"))?;
+		let mut data = Request::get(url)?.data()?;
+		mangaplus::decrypt_image(&mut data, key)?;
+		Ok(ImageRef::new(&data))
+	}
+}
+
 register_source!(
 	MangaDex,
 	Home,
 	ListingProvider,
+	DynamicFilters,
 	DynamicListings,
 	AlternateCoverProvider,
+	ImageRequestProvider,
+	PageImageProcessor,
+	BasicLoginHandler,
+	NotificationHandler,
 	DeepLinkHandler
 );
@@ -0,0 +1,235 @@
+// support for official chapters hosted on MangaPlus, which the MangaDex feed links to as external urls
+use aidoku::{
+	alloc::{String, Vec},
+	imports::{error::AidokuError, net::Request},
+	prelude::*,
+	Page, PageContent, PageContext, Result,
+};
+
+const API_URL: &str = "https://jumpg-webapi.tokyo-cdn.com/api";
+const VIEWER_URL: &str = "https://mangaplus.shueisha.co.jp/viewer/";
+
+// page context key for the key that a page image is encrypted with
+pub const ENCRYPTION_KEY: &str = "mangaPlusKey";
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MangaPlusPage {
+	pub url: String,
+	pub encryption_key: Option<String>,
+}
+
+// get the mangaplus chapter id from an external chapter url
+// ex: https://mangaplus.shueisha.co.jp/viewer/1000486
+pub fn chapter_id(url: &str) -> Option<&str> {
+	let id = url.strip_prefix(VIEWER_URL)?;
+	let end = id.find(['/', '?']).unwrap_or(id.len());
+	let id = &id[..end];
+	if !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()) {
+		Some(id)
+	} else {
+		None
+	}
+}
+
+pub fn is_mangaplus_url(url: &str) -> bool {
+	chapter_id(url).is_some()
+}
+
+pub fn get_page_list(chapter_id: &str) -> Result<Vec<Page>> {
+	let data = Request::get(format!(
+		"{API_URL}/manga_viewer?chapter_id={chapter_id}&split=yes&img_quality=super_high"
+	))?
+	.data()?;
+
+	Ok(parse_manga_viewer(&data)?
+		.into_iter()
+		.map(|page| Page {
+			content: match page.encryption_key {
+				Some(key) => {
+					let mut context = PageContext::new();
+					context.insert(String::from(ENCRYPTION_KEY), key);
+					PageContent::url_context(page.url, context)
+				}
+				None => PageContent::url(page.url),
+			},
+			..Default::default()
+		})
+		.collect())
+}
+
+// decrypt a page image by xoring it with the page's hex encoded key
+pub fn decrypt_image(data: &mut [u8], key: &str) -> Result<()> {
+	let key = (0..key.len())
+		.step_by(2)
+		.map(|i| {
+			key.get(i..i + 2)
+				.and_then(|byte| u8::from_str_radix(byte, 16).ok())
+		})
+		.collect::<Option<Vec<u8>>>()
+		.filter(|key| !key.is_empty())
+		.ok_or(AidokuError::message("Invalid MangaPlus encryption key"))?;
+	for (byte, key_byte) in data.iter_mut().zip(key.iter().cycle()) {
+		*byte ^= key_byte;
+	}
+	Ok(())
+}
+
+// parse the protobuf response of the manga viewer endpoint:
+//
+// Response { SuccessResult success = 1; ErrorResult error = 2; }
+// SuccessResult { MangaViewer manga_viewer = 10; }
+// ErrorResult { Popup english_popup = 2; }
+// Popup { string subject = 1; string body = 2; }
+// MangaViewer { repeated Page pages = 1; }
+// Page { MangaPage manga_page = 1; }
+// MangaPage { string image_url = 1; uint32 width = 2; uint32 height = 3; string encryption_key = 5; }
+pub fn parse_manga_viewer(data: &[u8]) -> Result<Vec<MangaPlusPage>> {
+	let invalid = || AidokuError::message("Invalid MangaPlus response");
+
+	let mut success = None;
+	for field in ProtoReader::new(data) {
+		match field.ok_or_else(invalid)? {
+			(1, WireValue::Bytes(bytes)) => success = Some(bytes),
+			(2, WireValue::Bytes(error)) => {
+				let message = ProtoReader::new(error)
+					.find_bytes(2)
+					.and_then(|popup| ProtoReader::new(popup).find_bytes(2))
+					.and_then(|body| core::str::from_utf8(body).ok())
+					.unwrap_or("MangaPlus returned an error");
+				return Err(AidokuError::message(message));
+			}
+			_ => {}
+		}
+	}
+
+	let viewer = success
+		.and_then(|success| ProtoReader::new(success).find_bytes(10))
+		.ok_or_else(invalid)?;
+
+	let mut pages = Vec::new();
+	for field in ProtoReader::new(viewer) {
+		let (1, WireValue::Bytes(page)) = field.ok_or_else(invalid)? else {
+			continue;
+		};
+		// pages without a manga page are banners, such as the last page
+		let Some(manga_page) = ProtoReader::new(page).find_bytes(1) else {
+			continue;
+		};
+		let mut url = None;
+		let mut encryption_key = None;
+		for field in ProtoReader::new(manga_page) {
+			match field.ok_or_else(invalid)? {
+				(1, WireValue::Bytes(value)) => {
+					url = core::str::from_utf8(value).ok().map(String::from);
+				}
+				(5, WireValue::Bytes(value)) => {
+					encryption_key = core::str::from_utf8(value)
+						.ok()
+						.filter(|key| !key.is_empty())
+						.map(String::from);
+				}
+				_ => {}
+			}
+		}
+		if let Some(url) = url {
+			pages.push(MangaPlusPage {
+				url,
+				encryption_key,
+			});
+		}
+	}
+
+	Ok(pages)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum WireValue<'a> {
+	// only length-delimited values are needed, so the others are skipped
+	Varint,
+	Fixed64,
+	Bytes(&'a [u8]),
+	Fixed32,
+}
+
+// a minimal reader for the fields of an encoded protobuf message
+struct ProtoReader<'a> {
+	data: &'a [u8],
+	pos: usize,
+}
+
+impl<'a> ProtoReader<'a> {
+	fn new(data: &'a [u8]) -> Self {
+		Self { data, pos: 0 }
+	}
+
+	fn read_varint(&mut self) -> Option<u64> {
+		let mut value = 0u64;
+		for shift in (0..64).step_by(7) {
+			let byte = *self.data.get(self.pos)?;
+			self.pos += 1;
+			value |= u64::from(byte & 0x7f) << shift;
+			if byte & 0x80 == 0 {
+				return Some(value);
+			}
+		}
+		None
+	}
+
+	fn read_slice(&mut self, len: usize) -> Option<&'a [u8]> {
+		let slice = self.data.get(self.pos..self.pos.checked_add(len)?)?;
+		self.pos += len;
+		Some(slice)
+	}
+
+	fn read_field(&mut self) -> Option<(u64, WireValue<'a>)> {
+		let key = self.read_varint()?;
+		let value = match key & 0x7 {
+			0 => {
+				self.read_varint()?;
+				WireValue::Varint
+			}
+			1 => {
+				self.read_slice(8)?;
+				WireValue::Fixed64
+			}
+			2 => {
+				let len = usize::try_from(self.read_varint()?).ok()?;
+				WireValue::Bytes(self.read_slice(len)?)
+			}
+			5 => {
+				self.read_slice(4)?;
+				WireValue::Fixed32
+			}
+			_ => return None,
+		};
+		Some((key >> 3, value))
+	}
+
+	// get the first length-delimited field with the given number
+	fn find_bytes(self, number: u64) -> Option<&'a [u8]> {
+		self.map_while(|field| field).find_map(|field| match field {
+			(n, WireValue::Bytes(bytes)) if n == number => Some(bytes),
+			_ => None,
+		})
+	}
+}
+
+// yields each field, or `None` once if the message is malformed
+impl<'a> Iterator for ProtoReader<'a> {
+	type Item = Option<(u64, WireValue<'a>)>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.pos >= self.data.len() {
+			return None;
+		}
+		let field = self.read_field();
+		if field.is_none() {
+			// stop after reporting the error
+			self.pos = self.data.len();
+		}
+		Some(field)
+	}
+}
+
+#[cfg(test)]
+mod test;
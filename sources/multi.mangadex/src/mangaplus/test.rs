@@ -0,0 +1,116 @@
+#![expect(clippy::unwrap_used)]
+
+use super::*;
+use aidoku_test::aidoku_test;
+
+// no recorded responses are checked in yet, so messages are encoded here following the
+// schema documented on parse_manga_viewer
+
+fn varint(mut value: u64, out: &mut Vec<u8>) {
+	while value >= 0x80 {
+		out.push((value as u8) | 0x80);
+		value >>= 7;
+	}
+	out.push(value as u8);
+}
+
+fn bytes_field(number: u64, value: &[u8]) -> Vec<u8> {
+	let mut out = Vec::new();
+	varint((number << 3) | 2, &mut out);
+	varint(value.len() as u64, &mut out);
+	out.extend_from_slice(value);
+	out
+}
+
+fn varint_field(number: u64, value: u64) -> Vec<u8> {
+	let mut out = Vec::new();
+	varint(number << 3, &mut out);
+	varint(value, &mut out);
+	out
+}
+
+fn message(fields: &[Vec<u8>]) -> Vec<u8> {
+	fields.concat()
+}
+
+fn manga_page(url: &str, encryption_key: &str) -> Vec<u8> {
+	let manga_page = message(&[
+		bytes_field(1, url.as_bytes()),
+		varint_field(2, 1000),
+		varint_field(3, 1500),
+		bytes_field(5, encryption_key.as_bytes()),
+	]);
+	bytes_field(1, &bytes_field(1, &manga_page))
+}
+
+fn manga_viewer(pages: &[Vec<u8>]) -> Vec<u8> {
+	bytes_field(1, &bytes_field(10, &pages.concat()))
+}
+
+#[aidoku_test]
+fn manga_viewer_pages() {
+	// the last page is a banner without a manga page
+	let banner = bytes_field(1, &bytes_field(2, &varint_field(1, 1)));
+	let data = manga_viewer(&[
+		manga_page("https://example.com/1.jpg", "0a0b"),
+		manga_page("https://example.com/2.jpg", ""),
+		banner,
+	]);
+	let pages = parse_manga_viewer(&data).unwrap();
+	assert_eq!(
+		pages,
+		[
+			MangaPlusPage {
+				url: "https://example.com/1.jpg".into(),
+				encryption_key: Some("0a0b".into()),
+			},
+			MangaPlusPage {
+				url: "https://example.com/2.jpg".into(),
+				encryption_key: None,
+			},
+		]
+	);
+}
+
+#[aidoku_test]
+fn manga_viewer_error() {
+	let popup = message(&[
+		bytes_field(1, b"Error"),
+		bytes_field(2, b"This chapter is unavailable."),
+	]);
+	let data = bytes_field(2, &message(&[varint_field(1, 1), bytes_field(2, &popup)]));
+	let error = parse_manga_viewer(&data).unwrap_err();
+	assert!(matches!(
+		error,
+		AidokuError::Message(message) if message == "This chapter is unavailable."
+	));
+}
+
+#[aidoku_test]
+fn manga_viewer_truncated() {
+	let data = manga_viewer(&[manga_page("https://example.com/1.jpg", "0a0b")]);
+	assert!(parse_manga_viewer(&data[..data.len() / 2]).is_err());
+}
+
+#[aidoku_test]
+fn decrypt_image_xor() {
+	let mut data = [0xff, 0xd8, 0xff, 0xe0, 0x00, 0x10];
+	decrypt_image(&mut data, "3c1c").unwrap();
+	assert_eq!(data, [0xc3, 0xc4, 0xc3, 0xfc, 0x3c, 0x0c]);
+	decrypt_image(&mut data, "3c1c").unwrap();
+	assert_eq!(data, [0xff, 0xd8, 0xff, 0xe0, 0x00, 0x10]);
+	assert!(decrypt_image(&mut data, "zz").is_err());
+}
+
+#[aidoku_test]
+fn external_chapter_id() {
+	assert_eq!(
+		chapter_id("https://mangaplus.shueisha.co.jp/viewer/1000486"),
+		Some("1000486")
+	);
+	assert_eq!(
+		chapter_id("https://mangaplus.shueisha.co.jp/viewer/1000486?lang=eng"),
+		Some("1000486")
+	);
+	assert_eq!(chapter_id("https://comikey.com/read/1000486"), None);
+}
@@ -1,6 +1,6 @@
-use crate::{settings, COVER_URL};
+use crate::{mangaplus, settings, COVER_URL};
 use aidoku::{
-	alloc::{String, Vec},
+	alloc::{string::ToString, String, Vec},
 	prelude::format,
 	Chapter, ContentRating, Manga, MangaStatus, Viewer,
 };
@@ -14,6 +14,81 @@ pub struct TokenResponse {
 	pub refresh_token: Option<String>,
 }
 
+// an oauth error from mangadex's auth server
+#[derive(Default, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct TokenErrorResponse {
+	pub error: String,
+	pub error_description: Option<String>,
+}
+
+// chapters from the last feed sync of a manga, stored in defaults
+#[derive(Default, Deserialize, Serialize, Debug, Clone)]
+pub struct FeedCache {
+	// the feed query parameters derived from settings when the cache was stored
+	pub settings: String,
+	pub chapters: Vec<CachedChapter>,
+	// volume covers for chapter thumbnails, only refetched on a full sync
+	#[serde(default)]
+	pub covers: Vec<DexCoverArt>,
+}
+
+// when a manga's feed cache was last synced, stored in defaults in sync order
+#[derive(Default, Deserialize, Serialize, Debug, Clone)]
+pub struct FeedCacheEntry {
+	pub manga_id: String,
+	pub synced_at: i64,
+}
+
+// empty fields are left out, since a cached feed can hold thousands of chapters
+#[derive(Default, Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct CachedChapter {
+	pub key: String,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub title: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub chapter_number: Option<f32>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub volume_number: Option<f32>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub date_uploaded: Option<i64>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub scanlators: Option<Vec<String>>,
+	// only stored for external chapters, since it's derived from the key otherwise
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub url: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub language: Option<String>,
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub group_ids: Vec<String>,
+	#[serde(skip_serializing_if = "core::ops::Not::not")]
+	pub unavailable: bool,
+}
+
+// search tags from /manga/tag, stored in defaults
+#[derive(Default, Deserialize, Serialize, Debug, Clone)]
+pub struct TagCache {
+	pub fetched_at: i64,
+	pub tags: Vec<CachedTag>,
+}
+
+#[derive(Default, Deserialize, Serialize, Debug, Clone)]
+pub struct CachedTag {
+	pub id: String,
+	pub group: String,
+	pub name: DexLocalizedString,
+}
+
+// basic details of a manga in the user's library, stored in defaults
+#[derive(Default, Deserialize, Serialize, Debug, Clone)]
+pub struct CachedManga {
+	pub key: String,
+	pub title: String,
+	pub cover: Option<String>,
+	pub updated_at: String,
+}
+
 #[derive(Default, Deserialize, Debug, Clone)]
 pub struct DexResponse<T> {
 	// pub result: &'a str,
@@ -36,30 +111,29 @@ pub struct DexRelationship<'a> {
 	pub attributes: Option<Map<String, Value>>,
 }
 
-#[derive(Default, Deserialize, Debug, Clone)]
-#[serde(rename_all = "kebab-case", default)]
-pub struct DexLocalizedString {
-	pub en: Option<String>,
-	pub ja: Option<String>,
-	pub ja_ro: Option<String>,
-}
+// a map of language codes to localized strings
+#[derive(Default, Deserialize, Serialize, Debug, Clone)]
+#[serde(transparent)]
+pub struct DexLocalizedString(Map<String, Value>);
 
 #[derive(Default, Deserialize, Debug, Clone)]
 pub struct DexTag {
-	// pub id: String,
+	pub id: String,
 	pub attributes: DexTagAttributes,
 }
 
 #[derive(Default, Deserialize, Debug, Clone)]
+#[serde(default)]
 pub struct DexTagAttributes {
 	pub name: DexLocalizedString,
+	pub group: String,
 }
 
-// generic data result without attribuets
+// an author, scanlation group, or custom list, without the attributes besides its name
 #[derive(Default, Deserialize, Debug, Clone)]
-pub struct DexData<'a> {
-	pub id: &'a str,
-	// pub relationships: Vec<DexRelationship<'a>>,
+pub struct DexNamedEntity {
+	pub id: String,
+	pub attributes: DexCustomListAttributes,
 }
 
 #[derive(Default, Deserialize, Debug, Clone)]
@@ -69,7 +143,7 @@ pub struct DexCustomList<'a> {
 	pub relationships: Vec<DexRelationship<'a>>,
 }
 
-#[derive(Default, Deserialize, Debug, Clone)]
+#[derive(Default, Deserialize, Serialize, Debug, Clone)]
 pub struct DexCoverArt {
 	// pub id: &'a str,
 	pub attributes: DexCoverArtAttributes,
@@ -109,21 +183,91 @@ pub struct DexCustomListAttributes {
 	pub name: String,
 }
 
-#[derive(Default, Deserialize, Debug, Clone)]
+#[derive(Default, Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase", default)]
 pub struct DexCoverArtAttributes {
 	pub file_name: String,
+	pub volume: Option<String>,
+	pub locale: String,
 }
 
 #[derive(Default, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase", default)]
 pub struct DexMangaAttributes {
 	pub title: DexLocalizedString,
+	pub alt_titles: Vec<DexLocalizedString>,
 	pub description: DexLocalizedString,
 	pub original_language: String,
 	pub content_rating: DexContentRating,
 	pub tags: Vec<DexTag>,
 	pub status: DexStatus,
+	pub updated_at: String,
+	pub links: Option<Map<String, Value>>,
+	pub available_translated_languages: Vec<String>,
+}
+
+// an error response (https://api.mangadex.org/docs/04-errors/)
+#[derive(Default, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct DexErrorResponse {
+	pub errors: Vec<DexError>,
+}
+
+#[derive(Default, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct DexError {
+	pub title: String,
+	pub detail: Option<String>,
+}
+
+#[derive(Default, Deserialize, Debug, Clone)]
+pub struct DexStatisticsResponse {
+	// statistics keyed by manga id
+	pub statistics: Map<String, Value>,
+}
+
+#[derive(Default, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct DexMangaStatistics {
+	pub rating: DexRating,
+	pub follows: Option<i64>,
+}
+
+#[derive(Default, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct DexRating {
+	pub average: Option<f32>,
+	pub bayesian: Option<f32>,
+}
+
+impl DexMangaStatistics {
+	// a line such as "Rating: 8.52 · Follows: 12,345"
+	pub fn summary(&self) -> Option<String> {
+		let rating = self
+			.rating
+			.bayesian
+			.or(self.rating.average)
+			.filter(|rating| *rating > 0.0)
+			.map(|rating| format!("Rating: {rating:.2}"));
+		let follows = self.follows.map(|follows| {
+			// group the digits in thousands
+			let digits = follows.to_string();
+			let mut grouped = String::new();
+			for (i, c) in digits.chars().enumerate() {
+				if i > 0 && (digits.len() - i) % 3 == 0 {
+					grouped.push(',');
+				}
+				grouped.push(c);
+			}
+			format!("Follows: {grouped}")
+		});
+		let parts = [rating, follows].into_iter().flatten().collect::<Vec<_>>();
+		if parts.is_empty() {
+			None
+		} else {
+			Some(parts.join(" · "))
+		}
+	}
 }
 
 #[derive(Default, Deserialize, Debug, Clone)]
@@ -142,33 +286,71 @@ pub struct DexChapterAttributes<'a> {
 	pub external_url: Option<Value>,
 	pub translated_language: &'a str,
 	pub publish_at: &'a str,
+	pub is_unavailable: bool,
+	pub pages: Option<i32>,
 }
 
 #[derive(Default, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase", default)]
-pub struct DexAtHomeResponse<'a> {
+pub struct DexAtHomeResponse {
 	pub base_url: String,
-	#[serde(borrow)]
-	pub chapter: DexAtHomeChapter<'a>,
+	pub chapter: DexAtHomeChapter,
 }
 
 #[derive(Default, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase", default)]
-pub struct DexAtHomeChapter<'a> {
-	pub hash: &'a str,
-	pub data: Option<Vec<&'a str>>,
-	pub data_saver: Option<Vec<&'a str>>,
+pub struct DexAtHomeChapter {
+	pub hash: String,
+	pub data: Option<Vec<String>>,
+	pub data_saver: Option<Vec<String>>,
 }
 
 impl DexLocalizedString {
 	pub fn get(&self) -> Option<String> {
-		if let Some(en) = &self.en {
-			Some(en.clone())
-		} else if let Some(ja_ro) = &self.ja_ro {
-			Some(ja_ro.clone())
-		} else {
-			self.ja.clone()
-		}
+		self.get_preferred(&["en", "ja-ro", "ja"])
+	}
+
+	// get the string for the first available language, or any string if none are available
+	pub fn get_preferred<T: AsRef<str>>(&self, languages: &[T]) -> Option<String> {
+		languages
+			.iter()
+			.find_map(|lang| self.get_lang(lang.as_ref()))
+			.or_else(|| {
+				self.0
+					.values()
+					.filter_map(|v| v.as_str())
+					.find(|v| !v.is_empty())
+					.map(String::from)
+			})
+	}
+
+	pub fn get_lang(&self, language: &str) -> Option<String> {
+		self.0
+			.get(language)
+			.and_then(|v| v.as_str())
+			.filter(|v| !v.is_empty())
+			.map(String::from)
+	}
+
+	pub fn values(&self) -> impl Iterator<Item = &str> {
+		self.0.values().filter_map(|v| v.as_str())
+	}
+}
+
+impl DexCoverArt {
+	pub fn volume_number(&self) -> Option<f32> {
+		self.attributes
+			.volume
+			.as_deref()
+			.and_then(|volume| volume.parse::<f32>().ok())
+	}
+
+	pub fn url(&self, manga_id: &str) -> String {
+		format!(
+			"{COVER_URL}/covers/{manga_id}/{}{}",
+			self.attributes.file_name,
+			settings::get_cover_quality()
+		)
 	}
 }
 
@@ -183,11 +365,97 @@ impl DexManga<'_> {
 	}
 
 	pub fn title(&self) -> Option<String> {
-		self.attributes.title.get()
+		// the main title only has one language, so look through the alt titles as well
+		let languages = settings::get_title_languages(&self.attributes.original_language);
+		languages
+			.iter()
+			.find_map(|lang| {
+				self.attributes.title.get_lang(lang).or_else(|| {
+					self.attributes
+						.alt_titles
+						.iter()
+						.find_map(|title| title.get_lang(lang))
+				})
+			})
+			.or_else(|| self.attributes.title.get())
+	}
+
+	pub fn alt_titles(&self) -> Vec<String> {
+		let title = self.title();
+		let mut alt_titles: Vec<String> = Vec::new();
+		for alt_title in self
+			.attributes
+			.alt_titles
+			.iter()
+			.chain(core::iter::once(&self.attributes.title))
+			.flat_map(|title| title.values())
+		{
+			if title.as_deref() != Some(alt_title) && !alt_titles.iter().any(|t| t == alt_title) {
+				alt_titles.push(String::from(alt_title));
+			}
+		}
+		alt_titles
+	}
+
+	// get tracker and official links, as (name, url) pairs
+	pub fn links(&self) -> Vec<(&'static str, String)> {
+		let Some(links) = &self.attributes.links else {
+			return Vec::new();
+		};
+		let link = |key: &str| links.get(key).and_then(|v| v.as_str());
+		let numeric = |value: &str| value.chars().all(|c| c.is_ascii_digit());
+
+		[
+			(
+				"AniList",
+				link("al").map(|id| format!("https://anilist.co/manga/{id}")),
+			),
+			(
+				"MyAnimeList",
+				link("mal").map(|id| format!("https://myanimelist.net/manga/{id}")),
+			),
+			(
+				"Kitsu",
+				link("kt").map(|id| format!("https://kitsu.app/manga/{id}")),
+			),
+			(
+				"MangaUpdates",
+				link("mu").map(|id| {
+					// old ids are numeric, newer ones are base36
+					if numeric(id) {
+						format!("https://www.mangaupdates.com/series.html?id={id}")
+					} else {
+						format!("https://www.mangaupdates.com/series/{id}")
+					}
+				}),
+			),
+			(
+				"Anime-Planet",
+				link("ap").map(|slug| format!("https://www.anime-planet.com/manga/{slug}")),
+			),
+			(
+				"NovelUpdates",
+				link("nu").map(|slug| format!("https://www.novelupdates.com/series/{slug}")),
+			),
+			(
+				"BookWalker",
+				link("bw").map(|path| format!("https://bookwalker.jp/{path}")),
+			),
+			("Official Raw", link("raw").map(String::from)),
+			("Official English", link("engtl").map(String::from)),
+			("Amazon", link("amz").map(String::from)),
+			("eBookJapan", link("ebj").map(String::from)),
+			("CDJapan", link("cdj").map(String::from)),
+		]
+		.into_iter()
+		.filter_map(|(name, url)| Some((name, url?)))
+		.collect()
 	}
 
 	pub fn description(&self) -> Option<String> {
-		self.attributes.description.get()
+		let mut languages = settings::get_dex_languages();
+		languages.push(String::from("en"));
+		self.attributes.description.get_preferred(&languages)
 	}
 
 	pub fn cover(&self) -> Option<String> {
@@ -285,7 +553,35 @@ impl From<DexManga<'_>> for Manga {
 			cover: val.cover(),
 			artists: Some(val.artists()),
 			authors: Some(val.authors()),
-			description: val.description(),
+			description: {
+				let mut sections = Vec::new();
+				if let Some(description) = val.description() {
+					sections.push(description);
+				}
+				let alt_titles = val.alt_titles();
+				if !alt_titles.is_empty() {
+					let alt_titles = alt_titles
+						.iter()
+						.map(|title| format!("• {title}"))
+						.collect::<Vec<_>>()
+						.join("\n");
+					sections.push(format!("Alternative Titles:\n{alt_titles}"));
+				}
+				let links = val.links();
+				if !links.is_empty() {
+					let links = links
+						.iter()
+						.map(|(name, url)| format!("• {name}: {url}"))
+						.collect::<Vec<_>>()
+						.join("\n");
+					sections.push(format!("Links:\n{links}"));
+				}
+				if sections.is_empty() {
+					None
+				} else {
+					Some(sections.join("\n\n"))
+				}
+			},
 			url: Some(val.url()),
 			tags: Some(tags),
 			status: val.status(),
@@ -301,8 +597,30 @@ impl<'a> DexChapter<'a> {
 		self.attributes.external_url.is_some()
 	}
 
+	// whether the chapter's pages were removed, such as for dmca takedowns
+	pub fn is_unavailable(&self) -> bool {
+		self.attributes.is_unavailable
+			|| (self.attributes.pages == Some(0) && !self.has_external_url())
+	}
+
+	pub fn external_url(&self) -> Option<&str> {
+		self.attributes
+			.external_url
+			.as_ref()
+			.and_then(|url| url.as_str())
+	}
+
+	// whether the chapter is an external chapter that can be read through mangaplus
+	pub fn is_mangaplus(&self) -> bool {
+		self.external_url().is_some_and(mangaplus::is_mangaplus_url)
+	}
+
 	pub fn url(&self) -> String {
-		format!("https://mangadex.org/chapter/{}", self.id)
+		if self.is_mangaplus() {
+			self.external_url().map(String::from).unwrap_or_default()
+		} else {
+			chapter_url(self.id)
+		}
 	}
 
 	pub fn manga_id(&self) -> Option<&'a str> {
@@ -315,6 +633,14 @@ impl<'a> DexChapter<'a> {
 		})
 	}
 
+	pub fn group_ids(&self) -> Vec<String> {
+		self.relationships
+			.iter()
+			.filter(|r| r.r#type == "scanlation_group")
+			.map(|r| String::from(r.id))
+			.collect()
+	}
+
 	pub fn scanlators(&self) -> Vec<String> {
 		let scanlation_groups: Vec<String> = self
 			.relationships
@@ -389,6 +715,16 @@ impl From<DexChapter<'_>> for Chapter {
 			val.attributes.title.clone()
 		};
 
+		let unavailable = val.is_unavailable();
+		let title = if unavailable {
+			Some(match title.filter(|t| !t.is_empty()) {
+				Some(title) => format!("{title} (Unavailable)"),
+				None => String::from("Unavailable"),
+			})
+		} else {
+			title
+		};
+
 		Chapter {
 			key: String::from(val.id),
 			title,
@@ -400,6 +736,88 @@ impl From<DexChapter<'_>> for Chapter {
 			scanlators: Some(val.scanlators()),
 			url: Some(val.url()),
 			language: Some(String::from(val.attributes.translated_language)),
+			locked: unavailable,
+			..Default::default()
+		}
+	}
+}
+
+impl From<DexTag> for CachedTag {
+	fn from(val: DexTag) -> Self {
+		Self {
+			id: val.id,
+			group: val.attributes.group,
+			name: val.attributes.name,
+		}
+	}
+}
+
+impl From<DexManga<'_>> for CachedManga {
+	fn from(val: DexManga<'_>) -> Self {
+		Self {
+			key: String::from(val.id),
+			title: val.title().unwrap_or_default(),
+			cover: val.cover(),
+			updated_at: val.attributes.updated_at,
+		}
+	}
+}
+
+impl From<CachedManga> for Manga {
+	fn from(val: CachedManga) -> Self {
+		Manga {
+			key: val.key,
+			title: val.title,
+			cover: val.cover,
+			..Default::default()
+		}
+	}
+}
+
+fn chapter_url(id: &str) -> String {
+	format!("https://mangadex.org/chapter/{id}")
+}
+
+impl From<&Chapter> for CachedChapter {
+	fn from(val: &Chapter) -> Self {
+		Self {
+			key: val.key.clone(),
+			title: val.title.clone(),
+			chapter_number: val.chapter_number,
+			volume_number: val.volume_number,
+			date_uploaded: val.date_uploaded,
+			scanlators: val.scanlators.clone(),
+			url: val.url.clone().filter(|url| *url != chapter_url(&val.key)),
+			language: val.language.clone(),
+			group_ids: Vec::new(),
+			unavailable: val.locked,
+		}
+	}
+}
+
+impl From<DexChapter<'_>> for CachedChapter {
+	fn from(val: DexChapter<'_>) -> Self {
+		let group_ids = val.group_ids();
+		let chapter: Chapter = val.into();
+		Self {
+			group_ids,
+			..CachedChapter::from(&chapter)
+		}
+	}
+}
+
+impl From<CachedChapter> for Chapter {
+	fn from(val: CachedChapter) -> Self {
+		Chapter {
+			key: val.key,
+			title: val.title,
+			chapter_number: val.chapter_number,
+			volume_number: val.volume_number,
+			date_uploaded: val.date_uploaded,
+			scanlators: val.scanlators,
+			url: val.url.or_else(|| Some(chapter_url(&val.key))),
+			language: val.language,
+			locked: val.unavailable,
 			..Default::default()
 		}
 	}
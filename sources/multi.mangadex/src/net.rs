@@ -0,0 +1,122 @@
+// sending api requests with rate limit handling and readable errors
+// (https://api.mangadex.org/docs/2-limitations/)
+use crate::models::DexErrorResponse;
+use aidoku::{
+	alloc::{String, Vec},
+	imports::{
+		error::AidokuError,
+		net::{set_rate_limit, Request, RequestError, Response, TimeUnit},
+		std::current_date,
+	},
+	prelude::*,
+	Result,
+};
+use core::sync::atomic::{AtomicBool, Ordering};
+
+const MAX_RETRIES: i32 = 2;
+// waits longer than this fail instead of blocking the source
+const MAX_BACKOFF: i64 = 60;
+
+// whether requests are currently limited to wait out a rate limit window
+static THROTTLED: AtomicBool = AtomicBool::new(false);
+
+// the global rate limit of 5 requests per second
+pub fn set_default_rate_limit() {
+	set_rate_limit(5, 1, TimeUnit::Seconds);
+}
+
+// hold the next request until the rate limit window is over
+fn throttle(wait: i64) {
+	set_rate_limit(
+		1,
+		wait.clamp(1, MAX_BACKOFF).try_into().unwrap_or(1),
+		TimeUnit::Seconds,
+	);
+	THROTTLED.store(true, Ordering::Relaxed);
+}
+
+fn unthrottle() {
+	if THROTTLED.swap(false, Ordering::Relaxed) {
+		set_default_rate_limit();
+	}
+}
+
+pub trait DexRequest {
+	// send a request, retrying when rate limited and turning api errors into messages
+	fn dex_send(self) -> Result<Response>;
+}
+
+impl DexRequest for Request {
+	fn dex_send(self) -> Result<Response> {
+		check_response(self.send())
+	}
+}
+
+// check the result of a sent request, resending it if it was rate limited
+pub fn check_response(response: core::result::Result<Response, RequestError>) -> Result<Response> {
+	let mut response = response?;
+	let mut retries = 0;
+	while response.status_code() == 429 && retries < MAX_RETRIES {
+		let wait = retry_after(&response).unwrap_or(1);
+		if wait > MAX_BACKOFF {
+			return Err(AidokuError::Message(format!(
+				"Rate limited by MangaDex, try again in {wait} seconds"
+			)));
+		}
+		throttle(wait);
+		response = response.into_request().send()?;
+		retries += 1;
+	}
+
+	// wait out the window before the next request once the endpoint's limit is used up
+	let remaining = response
+		.get_header("X-RateLimit-Remaining")
+		.and_then(|value| value.parse::<i32>().ok());
+	match retry_after(&response) {
+		Some(wait) if remaining == Some(0) && wait <= MAX_BACKOFF => throttle(wait),
+		_ => unthrottle(),
+	}
+
+	let status = response.status_code();
+	if !(200..300).contains(&status) {
+		return Err(AidokuError::Message(
+			error_message(&mut response)
+				.unwrap_or_else(|| format!("MangaDex returned status {status}")),
+		));
+	}
+
+	Ok(response)
+}
+
+// seconds until requests are allowed again
+fn retry_after(response: &Response) -> Option<i64> {
+	// mangadex sends the unix timestamp when the limit resets
+	if let Some(timestamp) = response
+		.get_header("X-RateLimit-Retry-After")
+		.and_then(|value| value.parse::<i64>().ok())
+	{
+		return Some((timestamp - current_date()).max(1));
+	}
+	response
+		.get_header("Retry-After")
+		.and_then(|value| value.parse::<i64>().ok())
+		.map(|seconds| seconds.max(1))
+}
+
+// get a message from the errors in an api error response
+fn error_message(response: &mut Response) -> Option<String> {
+	let errors = response.get_json::<DexErrorResponse>().ok()?.errors;
+	let messages = errors
+		.into_iter()
+		.map(|error| match error.detail {
+			Some(detail) if !detail.is_empty() => format!("{}: {detail}", error.title),
+			_ => error.title,
+		})
+		.filter(|message| !message.is_empty())
+		.collect::<Vec<_>>();
+	if messages.is_empty() {
+		None
+	} else {
+		Some(messages.join("\n"))
+	}
+}
@@ -1,10 +1,13 @@
-use crate::TokenResponse;
+use crate::{
+	at_home::CachedServer, CachedManga, FeedCache, FeedCacheEntry, TagCache, TokenResponse,
+};
 use aidoku::{
 	alloc::{string::String, vec::Vec},
 	imports::{
 		defaults::{defaults_get, defaults_get_json, defaults_set, DefaultValue},
 		error::AidokuError,
 	},
+	prelude::format,
 	Result,
 };
 use core::fmt::Write;
@@ -14,16 +17,87 @@ const LANGUAGES_KEY: &str = "languages";
 const COVER_QUALITY_KEY: &str = "coverQuality";
 const CONTENT_RATING_KEY: &str = "contentRating";
 const BLOCKED_UUIDS_KEY: &str = "blockedUUIDs";
+const BLOCKED_TAGS_KEY: &str = "blockedTags";
 const FORCE_PORT_KEY: &str = "standardHttpsPort";
 const DATA_SAVER_KEY: &str = "dataSaver";
-const TOKEN_KEY: &str = "login";
-const CODE_VERIFIER_KEY: &str = "login.codeVerifier";
+const MANGAPLUS_CHAPTERS_KEY: &str = "mangaPlusChapters";
+const TOKEN_KEY: &str = "login.token";
+const USERNAME_KEY: &str = "login.username";
+const CLIENT_ID_KEY: &str = "clientId";
+const CLIENT_SECRET_KEY: &str = "clientSecret";
+const TITLE_LANGUAGE_KEY: &str = "titleLanguage";
+const PREFERRED_GROUPS_KEY: &str = "preferredGroups";
+const COLLAPSE_CHAPTERS_KEY: &str = "collapseChapters";
+const HOME_LISTS_KEY: &str = "homeLists";
+const LIBRARY_SORT_KEY: &str = "librarySort";
+const FEED_CACHE_PREFIX: &str = "feedCache.";
+const FEED_CACHE_INDEX_KEY: &str = "feedCacheIndex";
+const LIBRARY_CACHE_PREFIX: &str = "libraryCache.";
+const TAG_CACHE_KEY: &str = "tagCache";
+const AT_HOME_SERVERS_KEY: &str = "atHomeServers";
+
+// the most manga feeds kept in defaults, dropping the least recently synced ones
+const MAX_FEED_CACHES: usize = 100;
+
+// the group that official mangaplus chapters are uploaded under
+const MANGAPLUS_GROUP_ID: &str = "4f1de6a2-f0c5-4ac5-bce5-02c7dbb67deb";
 
 pub fn get_languages() -> Result<Vec<String>> {
 	defaults_get::<Vec<String>>(LANGUAGES_KEY)
 		.ok_or(AidokuError::message("Unable to fetch languages"))
 }
 
+// get the selected languages as the codes used for mangadex localized strings
+pub fn get_dex_languages() -> Vec<String> {
+	defaults_get::<Vec<String>>(LANGUAGES_KEY)
+		.unwrap_or_default()
+		.iter()
+		.map(|lang| {
+			String::from(match lang.as_str() {
+				"zh-Hans" => "zh",
+				"zh-Hant" => "zh-hk",
+				"pt-BR" => "pt-br",
+				"es-419" => "es-la",
+				lang => lang,
+			})
+		})
+		.collect()
+}
+
+// get the language codes to pick a manga title from, in order of preference
+pub fn get_title_languages(original_language: &str) -> Vec<String> {
+	let romanized = format!("{original_language}-ro");
+	match defaults_get::<String>(TITLE_LANGUAGE_KEY)
+		.unwrap_or_default()
+		.as_str()
+	{
+		"original" => Vec::from([
+			String::from(original_language),
+			romanized,
+			String::from("en"),
+		]),
+		"romanized" => Vec::from([
+			romanized,
+			String::from("en"),
+			String::from(original_language),
+		]),
+		"en" => Vec::from([
+			String::from("en"),
+			romanized,
+			String::from(original_language),
+		]),
+		_ => {
+			let mut languages = get_dex_languages();
+			languages.extend([
+				String::from("en"),
+				romanized,
+				String::from(original_language),
+			]);
+			languages
+		}
+	}
+}
+
 pub fn get_languages_with_key(key: &str) -> Result<String> {
 	Ok(defaults_get::<Vec<String>>(LANGUAGES_KEY)
 		.ok_or(AidokuError::message("Unable to fetch languages"))?
@@ -53,9 +127,12 @@ pub fn get_content_ratings_list() -> Result<Vec<String>> {
 }
 
 pub fn get_blocked_uuids() -> Result<String> {
+	let mangaplus_chapters = get_mangaplus_chapters();
 	Ok(defaults_get::<Vec<String>>(BLOCKED_UUIDS_KEY)
 		.unwrap_or_default()
 		.iter()
+		// mangaplus is blocked by default, but shouldn't be when its chapters are enabled
+		.filter(|value| !mangaplus_chapters || value.trim() != MANGAPLUS_GROUP_ID)
 		.fold(String::new(), |mut output, value| {
 			let _ = write!(
 				output,
@@ -65,6 +142,33 @@ pub fn get_blocked_uuids() -> Result<String> {
 		}))
 }
 
+pub fn get_blocked_tags() -> String {
+	defaults_get::<Vec<String>>(BLOCKED_TAGS_KEY)
+		.unwrap_or_default()
+		.iter()
+		.fold(String::new(), |mut output, value| {
+			let _ = write!(output, "&excludedTags[]={value}");
+			output
+		})
+}
+
+pub fn get_preferred_groups() -> Vec<String> {
+	defaults_get::<Vec<String>>(PREFERRED_GROUPS_KEY)
+		.unwrap_or_default()
+		.into_iter()
+		.map(|id| String::from(id.trim()))
+		.filter(|id| !id.is_empty())
+		.collect()
+}
+
+pub fn get_collapse_chapters() -> bool {
+	defaults_get::<bool>(COLLAPSE_CHAPTERS_KEY).unwrap_or(false)
+}
+
+pub fn get_mangaplus_chapters() -> bool {
+	defaults_get::<bool>(MANGAPLUS_CHAPTERS_KEY).unwrap_or(false)
+}
+
 pub fn get_force_port() -> bool {
 	defaults_get::<bool>(FORCE_PORT_KEY).unwrap_or(false)
 }
@@ -77,6 +181,19 @@ pub fn get_cover_quality() -> String {
 	defaults_get::<String>(COVER_QUALITY_KEY).unwrap_or_default()
 }
 
+pub fn get_home_lists() -> Vec<String> {
+	defaults_get::<Vec<String>>(HOME_LISTS_KEY)
+		.unwrap_or_default()
+		.into_iter()
+		.map(|id| String::from(id.trim()))
+		.filter(|id| !id.is_empty())
+		.collect()
+}
+
+pub fn get_library_sort() -> String {
+	defaults_get::<String>(LIBRARY_SORT_KEY).unwrap_or_default()
+}
+
 pub fn is_logged_in() -> bool {
 	defaults_get_json::<TokenResponse>(TOKEN_KEY).is_ok()
 }
@@ -93,6 +210,97 @@ pub fn clear_token() {
 	defaults_set(TOKEN_KEY, DefaultValue::Null);
 }
 
-pub fn get_code_verifier() -> Option<String> {
-	defaults_get::<String>(CODE_VERIFIER_KEY)
+// whether the app still has login credentials stored
+pub fn has_login_credentials() -> bool {
+	defaults_get::<String>(USERNAME_KEY).is_some()
+}
+
+// the personal api client used to log in
+pub fn get_client() -> Result<(String, String)> {
+	let client_id = defaults_get::<String>(CLIENT_ID_KEY)
+		.map(|id| String::from(id.trim()))
+		.filter(|id| !id.is_empty())
+		.ok_or(AidokuError::message("Missing API client ID"))?;
+	let client_secret = defaults_get::<String>(CLIENT_SECRET_KEY)
+		.map(|secret| String::from(secret.trim()))
+		.filter(|secret| !secret.is_empty())
+		.ok_or(AidokuError::message("Missing API client secret"))?;
+	Ok((client_id, client_secret))
+}
+
+// get a manga's cached feed along with when it was last synced
+pub fn get_feed_cache(manga_id: &str) -> Option<(i64, FeedCache)> {
+	let synced_at = get_feed_cache_index()
+		.into_iter()
+		.find(|entry| entry.manga_id == manga_id)?
+		.synced_at;
+	let cache = defaults_get_json::<FeedCache>(&format!("{FEED_CACHE_PREFIX}{manga_id}")).ok()?;
+	Some((synced_at, cache))
+}
+
+pub fn set_feed_cache(manga_id: &str, cache: &FeedCache) {
+	if let Ok(value) = serde_json::to_string(cache) {
+		defaults_set(
+			&format!("{FEED_CACHE_PREFIX}{manga_id}"),
+			DefaultValue::String(value),
+		);
+	}
+}
+
+// record when a manga's feed was synced, evicting the caches synced longest ago
+pub fn set_feed_synced(manga_id: &str, synced_at: i64) {
+	let mut index = get_feed_cache_index();
+	index.retain(|entry| entry.manga_id != manga_id);
+	index.push(FeedCacheEntry {
+		manga_id: String::from(manga_id),
+		synced_at,
+	});
+	if index.len() > MAX_FEED_CACHES {
+		for entry in index.drain(..index.len() - MAX_FEED_CACHES) {
+			defaults_set(
+				&format!("{FEED_CACHE_PREFIX}{}", entry.manga_id),
+				DefaultValue::Null,
+			);
+		}
+	}
+	if let Ok(value) = serde_json::to_string(&index) {
+		defaults_set(FEED_CACHE_INDEX_KEY, DefaultValue::String(value));
+	}
+}
+
+fn get_feed_cache_index() -> Vec<FeedCacheEntry> {
+	defaults_get_json::<Vec<FeedCacheEntry>>(FEED_CACHE_INDEX_KEY).unwrap_or_default()
+}
+
+pub fn get_library_cache(status: &str) -> Option<Vec<CachedManga>> {
+	defaults_get_json::<Vec<CachedManga>>(&format!("{LIBRARY_CACHE_PREFIX}{status}")).ok()
+}
+
+pub fn set_library_cache(status: &str, library: &[CachedManga]) {
+	if let Ok(value) = serde_json::to_string(library) {
+		defaults_set(
+			&format!("{LIBRARY_CACHE_PREFIX}{status}"),
+			DefaultValue::String(value),
+		);
+	}
+}
+
+pub fn get_tag_cache() -> Option<TagCache> {
+	defaults_get_json::<TagCache>(TAG_CACHE_KEY).ok()
+}
+
+pub fn set_tag_cache(cache: &TagCache) {
+	if let Ok(value) = serde_json::to_string(cache) {
+		defaults_set(TAG_CACHE_KEY, DefaultValue::String(value));
+	}
+}
+
+pub fn get_at_home_servers() -> Vec<CachedServer> {
+	defaults_get_json::<Vec<CachedServer>>(AT_HOME_SERVERS_KEY).unwrap_or_default()
+}
+
+pub fn set_at_home_servers(servers: &[CachedServer]) {
+	if let Ok(value) = serde_json::to_string(servers) {
+		defaults_set(AT_HOME_SERVERS_KEY, DefaultValue::String(value));
+	}
 }
@@ -1,6 +1,6 @@
-use super::{helper::ElementImageAttr, parser, Params};
+use super::{helper::ElementImageAttr, parser, Params, RELATED_LISTING_PREFIX};
 use aidoku::{
-	alloc::{borrow::Cow, String, Vec},
+	alloc::{String, Vec},
 	helpers::uri::{encode_uri_component, QueryParameters},
 	imports::{
 		canvas::ImageRef,
@@ -10,7 +10,7 @@ use aidoku::{
 		std::send_partial_result,
 	},
 	prelude::*,
-	Chapter, DeepLinkResult, FilterValue, HomeComponent, HomeComponentValue, HomeLayout,
+	Chapter, DeepLinkResult, Filter, FilterValue, HomeComponent, HomeComponentValue, HomeLayout,
 	ImageResponse, Listing, Manga, MangaPageResult, MangaWithChapter, Page, PageContent,
 	PageContext, Result,
 };
@@ -20,19 +20,6 @@ pub trait Impl {
 
 	fn params(&self) -> Params;
 
-	fn get_sort_id(&self, index: i32) -> Cow<'static, str> {
-		match index {
-			0 => "default",
-			1 => "latest-updated",
-			2 => "score",
-			3 => "name-az",
-			4 => "release-date",
-			5 => "most-viewed",
-			_ => "default",
-		}
-		.into()
-	}
-
 	fn get_search_manga_list(
 		&self,
 		params: &Params,
@@ -54,15 +41,21 @@ pub trait Impl {
 			(params.set_default_filters)(&mut qs);
 			for filter in filters {
 				match filter {
-					FilterValue::Sort { index, .. } => {
-						qs.set("sort", Some(self.get_sort_id(index).as_ref()));
-					}
 					FilterValue::Select { id, value } => {
 						qs.set(&id, Some(&value));
 					}
 					// genres
-					FilterValue::MultiSelect { included, .. } => {
-						qs.set("genres", Some(&included.join(",")));
+					FilterValue::MultiSelect { id, included, .. } => {
+						qs.set(&id, Some(&included.join(",")));
+					}
+					// the year range maps to the start and end year selects
+					FilterValue::Range { id, from, to } if id == "year" => {
+						if let Some(from) = from {
+							qs.set("sy", Some(&format!("{}", from as i32)));
+						}
+						if let Some(to) = to {
+							qs.set("ey", Some(&format!("{}", to as i32)));
+						}
 					}
 					_ => {}
 				}
@@ -92,6 +85,18 @@ pub trait Impl {
 		})
 	}
 
+	fn get_dynamic_filters(&self, params: &Params) -> Result<Vec<Filter>> {
+		let filters = Request::get(format!("{}/filter", params.base_url))
+			.and_then(|req| req.html())
+			.map(|html| parser::parse_filters(&html))
+			.unwrap_or_default();
+		if filters.is_empty() {
+			Ok((params.get_fallback_filters)())
+		} else {
+			Ok(filters)
+		}
+	}
+
 	fn get_manga_update(
 		&self,
 		params: &Params,
@@ -176,6 +181,14 @@ pub trait Impl {
 		listing: Listing,
 		page: i32,
 	) -> Result<MangaPageResult> {
+		if let Some(manga_key) = listing.id.strip_prefix(RELATED_LISTING_PREFIX) {
+			let html = Request::get(format!("{}{manga_key}", params.base_url))?.html()?;
+			return Ok(MangaPageResult {
+				entries: parser::parse_related(&html, &params.base_url),
+				has_next_page: false,
+			});
+		}
+
 		let url = format!(
 			"{}/{}?{}={page}",
 			params.base_url, listing.id, params.page_param
@@ -195,34 +208,37 @@ pub trait Impl {
 		let mut components = Vec::new();
 
 		// header
-		if let Some(slider_elements) =
-			html.select("#slider .deslide-item:not(.swiper-slide-duplicate)")
-		{
+		let spotlight = html
+			.select("#slider .deslide-item:not(.swiper-slide-duplicate)")
+			.map(|slider_elements| {
+				slider_elements
+					.filter_map(|el| {
+						let link = el.select_first(".desi-head-title a")?;
+						let link_href = link.attr("href")?;
+						Some(Manga {
+							key: link_href
+								.strip_prefix(params.base_url.as_ref())
+								.map(|s| s.into())
+								.unwrap_or(link_href),
+							title: link.attr("title")?,
+							cover: el
+								.select_first(".deslide-poster img")
+								.and_then(|e| e.attr("src")),
+							description: el
+								.select_first(".sc-detail > .scd-item")
+								.and_then(|e| e.text()),
+							tags: el
+								.select(".sc-detail > .scd-genres > span")
+								.map(|els| els.filter_map(|e| e.text()).collect()),
+							..Default::default()
+						})
+					})
+					.collect::<Vec<_>>()
+			});
+		if let Some(entries) = spotlight {
 			components.push(HomeComponent {
 				value: HomeComponentValue::BigScroller {
-					entries: slider_elements
-						.filter_map(|el| {
-							let link = el.select_first(".desi-head-title a")?;
-							let link_href = link.attr("href")?;
-							Some(Manga {
-								key: link_href
-									.strip_prefix(params.base_url.as_ref())
-									.map(|s| s.into())
-									.unwrap_or(link_href),
-								title: link.attr("title")?,
-								cover: el
-									.select_first(".deslide-poster img")
-									.and_then(|e| e.attr("src")),
-								description: el
-									.select_first(".sc-detail > .scd-item")
-									.and_then(|e| e.text()),
-								tags: el
-									.select(".sc-detail > .scd-genres > span")
-									.map(|els| els.filter_map(|e| e.text()).collect()),
-								..Default::default()
-							})
-						})
-						.collect(),
+					entries,
 					auto_scroll_interval: Some(5.0),
 				},
 				..Default::default()
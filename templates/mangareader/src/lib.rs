@@ -4,9 +4,9 @@ use aidoku::{
 	helpers::uri::QueryParameters,
 	imports::{canvas::ImageRef, html::Element, net::Request},
 	prelude::*,
-	Chapter, DeepLinkHandler, DeepLinkResult, FilterValue, Home, HomeLayout, ImageRequestProvider,
-	ImageResponse, Listing, ListingProvider, Manga, MangaPageResult, Page, PageContext,
-	PageImageProcessor, Result, Source,
+	Chapter, DeepLinkHandler, DeepLinkResult, DynamicFilters, Filter, FilterValue, Home,
+	HomeLayout, ImageRequestProvider, ImageResponse, Listing, ListingProvider, Manga,
+	MangaPageResult, Page, PageContext, PageImageProcessor, Result, Source,
 };
 
 mod helper;
@@ -15,18 +15,46 @@ pub mod parser;
 
 pub use imp::Impl;
 
+// prefix for the listing of titles related to a manga, followed by the manga key
+pub const RELATED_LISTING_PREFIX: &str = "related:";
+
+/// Returns the listing of titles related to the given manga.
+pub fn related_listing(params: &Params, manga: &Manga) -> Listing {
+	Listing {
+		id: format!("{RELATED_LISTING_PREFIX}{}", manga.key),
+		name: (params.get_related_listing_name)(&manga.title),
+		..Default::default()
+	}
+}
+
+/// Returns the Aidoku language code for a language used in the site's list ids.
+pub fn language_code(lang: &str) -> String {
+	String::from(match lang {
+		"zh" => "zh-Hans",
+		lang => lang,
+	})
+}
+
 pub struct Params {
 	pub base_url: Cow<'static, str>,
 	pub search_path: Cow<'static, str>,
 	pub search_param: Cow<'static, str>,
 	pub page_param: Cow<'static, str>,
 	pub page_selector: Cow<'static, str>,
-	// css selector for chapter list items (typically contained in #{lang}-chapters or #{lang}-chaps)
-	pub get_chapter_selector: fn() -> Cow<'static, str>,
-	// the language of a chapter
+	// css selector for the per-language chapter lists (typically #{lang}-chapters or #{lang}-chaps)
+	pub chapter_list_selector: Cow<'static, str>,
+	// css selector for the per-language volume lists (typically #{lang}-volumes)
+	pub volume_list_selector: Cow<'static, str>,
+	// css selector for volume entries within a volume list
+	pub volume_item_selector: Cow<'static, str>,
+	// the language of the chapters in a chapter or volume list element
 	pub get_chapter_language: fn(&Element) -> String,
 	// path added to base url for page list ajax request
 	pub get_page_url_path: fn(&str) -> String,
+	// name of the listing of titles related to a manga, given its title
+	pub get_related_listing_name: fn(&str) -> String,
+	// filters used when the site's filter form can't be loaded or parsed
+	pub get_fallback_filters: fn() -> Vec<Filter>,
 	pub set_default_filters: fn(&mut QueryParameters) -> (),
 }
 
@@ -38,9 +66,17 @@ impl Default for Params {
 			search_param: "keyword".into(),
 			page_param: "page".into(),
 			page_selector: ".container-reader-chapter > div > img".into(),
-			get_chapter_selector: || "#en-chapters > li".into(),
-			get_chapter_language: |_| "en".into(),
+			chapter_list_selector: "[id$=-chapters]".into(),
+			volume_list_selector: "[id$=-volumes]".into(),
+			volume_item_selector: ".item".into(),
+			get_chapter_language: |el| {
+				el.attr("id")
+					.and_then(|id| id.split('-').next().map(language_code))
+					.unwrap_or_else(|| "en".into())
+			},
 			get_page_url_path: |chapter_id| format!("//ajax/image/list/{chapter_id}?mode=vertical"),
+			get_related_listing_name: |title| format!("Related to {title}"),
+			get_fallback_filters: Vec::new,
 			set_default_filters: |_| {},
 		}
 	}
@@ -83,6 +119,12 @@ impl<T: Impl> Source for MangaReader<T> {
 	}
 }
 
+impl<T: Impl> DynamicFilters for MangaReader<T> {
+	fn get_dynamic_filters(&self) -> Result<Vec<Filter>> {
+		self.inner.get_dynamic_filters(&self.params)
+	}
+}
+
 impl<T: Impl> ListingProvider for MangaReader<T> {
 	fn get_manga_list(&self, listing: Listing, page: i32) -> Result<MangaPageResult> {
 		self.inner.get_manga_list(&self.params, listing, page)
@@ -1,9 +1,13 @@
 use crate::{helper::ElementImageAttr, Params};
 use aidoku::{
-	alloc::{borrow::ToOwned, String, Vec},
-	imports::html::Document,
+	alloc::{borrow::ToOwned, collections::BTreeSet, String, Vec},
+	imports::{
+		defaults::defaults_get,
+		html::{Document, Element},
+	},
 	prelude::*,
-	AidokuError, Chapter, ContentRating, Manga, MangaStatus, Result, Viewer,
+	AidokuError, Chapter, ContentRating, Filter, Manga, MangaStatus, MultiSelectFilter,
+	RangeFilter, Result, SelectFilter, Viewer,
 };
 
 pub fn parse_response<T: AsRef<str>>(
@@ -118,59 +122,144 @@ pub fn parse_manga_details(manga: &mut Manga, html: &Document) -> Result<()> {
 }
 
 pub fn parse_manga_chapters(html: &Document, params: &Params) -> Option<Vec<Chapter>> {
-	html.select((params.get_chapter_selector)()).map(|els| {
-		let mut c = els
-			.filter_map(|el| {
-				let link = el.select_first("a")?;
-				let url = link.attr("abs:href")?;
-				let mut key: String = url.strip_prefix(params.base_url.as_ref())?.into();
-				if let Some(id) = el.attr("data-id") {
-					key.push_str(&format!("#{id}"));
-				}
-				let mut title = link.select_first(".name").and_then(|el| el.text());
-				let chapter_number =
-					title
-						.as_ref()
-						.and_then(|title| title.find(':'))
-						.and_then(|colon| {
-							let chapter_num_text = &title.as_ref().unwrap()[..colon].to_owned();
-							title = Some(title.as_ref().unwrap()[colon + 1..].trim().into());
-							chapter_num_text
-								.chars()
-								.filter(|c| c.is_ascii_digit() || *c == '.')
-								.collect::<String>()
-								.parse::<f32>()
-								.ok()
-						});
-				if title.as_ref().is_some_and(|t| {
-					*t == format!("Chapter {}", chapter_number.unwrap_or_default())
-						|| *t == format!("第{}話", chapter_number.unwrap_or_default())
-						|| *t == format!("第 {} 話", chapter_number.unwrap_or_default())
-						|| *t == format!("【第 {} 話】", chapter_number.unwrap_or_default())
-				}) {
-					title = None;
-				}
-				let language = (params.get_chapter_language)(&el);
-				Some(Chapter {
-					key,
-					title,
-					chapter_number,
-					url: Some(url),
-					language: language.into(),
-					..Default::default()
-				})
-			})
-			.collect::<Vec<_>>();
-		// sort combined chapters by chapter number
-		// since separate languages are grouped together by default
-		c.sort_by(|a, b| {
-			let a_num = a.chapter_number.unwrap_or(-1.0);
-			let b_num = b.chapter_number.unwrap_or(-1.0);
-			b_num
-				.partial_cmp(&a_num)
-				.unwrap_or(core::cmp::Ordering::Equal)
+	// languages selected in the source settings (all languages if unset)
+	let languages =
+		defaults_get::<Vec<String>>("chapterLanguages").filter(|langs| !langs.is_empty());
+	let (show_chapters, show_volumes) = match defaults_get::<String>("chapterType").as_deref() {
+		Some("volumes") => (false, true),
+		Some("both") => (true, true),
+		_ => (true, false),
+	};
+
+	let mut chapters = Vec::new();
+	if show_chapters {
+		chapters.extend(parse_chapter_lists(
+			html,
+			&params.chapter_list_selector,
+			"li",
+			params,
+			languages.as_deref(),
+			parse_chapter,
+		)?);
+	}
+	if show_volumes {
+		chapters.extend(
+			parse_chapter_lists(
+				html,
+				&params.volume_list_selector,
+				&params.volume_item_selector,
+				params,
+				languages.as_deref(),
+				parse_volume,
+			)
+			.unwrap_or_default(),
+		);
+	}
+	Some(chapters)
+}
+
+fn parse_chapter_lists(
+	html: &Document,
+	list_selector: &str,
+	item_selector: &str,
+	params: &Params,
+	languages: Option<&[String]>,
+	parse_item: fn(&Element, &Params, &str) -> Option<Chapter>,
+) -> Option<Vec<Chapter>> {
+	html.select(list_selector).map(|lists| {
+		let mut chapters = Vec::new();
+		for list in lists {
+			let language = (params.get_chapter_language)(&list);
+			if languages.is_some_and(|langs| !langs.contains(&language)) {
+				continue;
+			}
+			let Some(items) = list.select(item_selector) else {
+				continue;
+			};
+			let mut c = items
+				.filter_map(|el| parse_item(&el, params, &language))
+				.collect::<Vec<_>>();
+			// the same chapter can be listed more than once
+			let mut seen_keys = BTreeSet::new();
+			c.retain(|chapter| seen_keys.insert(chapter.key.clone()));
+			// sort each language's chapters by chapter (or volume) number
+			c.sort_by(|a, b| {
+				let a_num = a.chapter_number.or(a.volume_number).unwrap_or(-1.0);
+				let b_num = b.chapter_number.or(b.volume_number).unwrap_or(-1.0);
+				b_num
+					.partial_cmp(&a_num)
+					.unwrap_or(core::cmp::Ordering::Equal)
+			});
+			chapters.extend(c);
+		}
+		chapters
+	})
+}
+
+fn parse_chapter(el: &Element, params: &Params, language: &str) -> Option<Chapter> {
+	let link = el.select_first("a")?;
+	let url = link.attr("abs:href")?;
+	let mut key: String = url.strip_prefix(params.base_url.as_ref())?.into();
+	if let Some(id) = el.attr("data-id") {
+		key.push_str(&format!("#{id}"));
+	}
+	let mut title = link.select_first(".name").and_then(|el| el.text());
+	let chapter_number = title
+		.as_ref()
+		.and_then(|title| title.find(':'))
+		.and_then(|colon| {
+			let chapter_num_text = &title.as_ref().unwrap()[..colon].to_owned();
+			title = Some(title.as_ref().unwrap()[colon + 1..].trim().into());
+			chapter_num_text
+				.chars()
+				.filter(|c| c.is_ascii_digit() || *c == '.')
+				.collect::<String>()
+				.parse::<f32>()
+				.ok()
 		});
-		c
+	if title.as_ref().is_some_and(|t| {
+		*t == format!("Chapter {}", chapter_number.unwrap_or_default())
+			|| *t == format!("第{}話", chapter_number.unwrap_or_default())
+			|| *t == format!("第 {} 話", chapter_number.unwrap_or_default())
+			|| *t == format!("【第 {} 話】", chapter_number.unwrap_or_default())
+	}) {
+		title = None;
+	}
+	Some(Chapter {
+		key,
+		title,
+		chapter_number,
+		url: Some(url),
+		language: Some(language.into()),
+		..Default::default()
+	})
+}
+
+fn parse_volume(el: &Element, params: &Params, language: &str) -> Option<Chapter> {
+	let link = el.select_first("a[href]")?;
+	let url = link.attr("abs:href")?;
+	let mut key: String = url.strip_prefix(params.base_url.as_ref())?.into();
+	// volumes without an id get their reading id from the reader page
+	if let Some(id) = el.attr("data-id").or_else(|| link.attr("data-id")) {
+		key.push_str(&format!("#{id}"));
+	}
+	// ex: "VOL 3" or ".../en/volume-3"
+	let volume_number = el
+		.select_first(".tick-vol")
+		.and_then(|e| e.text())
+		.unwrap_or_else(|| url.rsplit('/').next().unwrap_or_default().into())
+		.chars()
+		.filter(|c| c.is_ascii_digit() || *c == '.')
+		.collect::<String>()
+		.parse::<f32>()
+		.ok();
+	Some(Chapter {
+		key,
+		volume_number,
+		thumbnail: el.select_first("img").and_then(|img| img.img_attr()),
+		url: Some(url),
+		language: Some(language.into()),
+		..Default::default()
 	})
 }
 
@@ -193,3 +282,127 @@ pub fn parse_manga_list(html: &Document, base_url: &str) -> Vec<Manga> {
 		})
 		.unwrap_or_default()
 }
+
+pub fn parse_related(html: &Document, base_url: &str) -> Vec<Manga> {
+	// the "you may also like" block is the sidebar section that isn't the ranking chart
+	html.select("#main-sidebar > section:not(:has(#chart-today)) .featured-block-ul > ul > li")
+		.map(|els| {
+			els.filter_map(|e| {
+				let link_href = e.select_first("a.manga-poster")?.attr("abs:href")?;
+				Some(Manga {
+					key: link_href
+						.strip_prefix(base_url)
+						.map(|s| s.into())
+						.unwrap_or(link_href),
+					title: e.select_first(".manga-name")?.text()?,
+					cover: e.select_first(".manga-poster img")?.img_attr(),
+					..Default::default()
+				})
+			})
+			.collect()
+		})
+		.unwrap_or_default()
+}
+
+pub fn parse_filters(html: &Document) -> Vec<Filter> {
+	let Some(form) = html.select_first("#filter-form, form[action*=filter]") else {
+		return Vec::new();
+	};
+
+	let mut filters = Vec::new();
+	let mut sort_filter = None;
+	if let Some(items) = form.select(".cmf-item") {
+		for item in items {
+			let title = item.select_first(".cmf-title").and_then(|el| el.text());
+			let Some(selects) = item.select("select[name]") else {
+				continue;
+			};
+			for select in selects {
+				let Some(name) = select.attr("name") else {
+					continue;
+				};
+				let (options, ids): (Vec<_>, Vec<_>) = select
+					.select("option")
+					.map(|els| {
+						els.filter_map(|el| {
+							Some((el.text()?, el.attr("value").unwrap_or_default()))
+						})
+						.unzip()
+					})
+					.unwrap_or_default();
+				if options.is_empty() {
+					continue;
+				}
+				match name.as_str() {
+					// start and end year selects are combined into a single range
+					"sy" => {
+						let years = ids
+							.iter()
+							.filter_map(|id| id.parse::<f32>().ok())
+							.collect::<Vec<_>>();
+						filters.push(
+							RangeFilter {
+								id: "year".into(),
+								title: title.clone().map(Into::into),
+								min: years.iter().copied().reduce(f32::min),
+								max: years.iter().copied().reduce(f32::max),
+								decimal: false,
+								..Default::default()
+							}
+							.into(),
+						);
+					}
+					"ey" | "sm" | "em" | "sd" | "ed" => {}
+					_ => {
+						let is_sort = name == "sort";
+						let filter: Filter = SelectFilter {
+							id: name.into(),
+							title: title.clone().map(Into::into),
+							options: options.into_iter().map(Into::into).collect(),
+							ids: Some(ids.into_iter().map(Into::into).collect()),
+							..Default::default()
+						}
+						.into();
+						// keep sort at the top of the filter list
+						if is_sort {
+							sort_filter = Some(filter);
+						} else {
+							filters.push(filter);
+						}
+					}
+				}
+			}
+		}
+	}
+
+	let (genres, genre_ids): (Vec<_>, Vec<_>) = form
+		.select(".f-genre-item[data-id]")
+		.map(|els| {
+			els.filter_map(|el| Some((el.text()?, el.attr("data-id")?)))
+				.unzip()
+		})
+		.unwrap_or_default();
+	if !genres.is_empty() {
+		filters.push(
+			MultiSelectFilter {
+				id: "genres".into(),
+				title: form
+					.select_first(".cmf-item:has(.f-genre-item) .cmf-title")
+					.and_then(|el| el.text())
+					.map(Into::into),
+				is_genre: true,
+				uses_tag_style: true,
+				options: genres.into_iter().map(Into::into).collect(),
+				ids: Some(genre_ids.into_iter().map(Into::into).collect()),
+				..Default::default()
+			}
+			.into(),
+		);
+	}
+
+	if let Some(sort_filter) = sort_filter {
+		filters.insert(0, sort_filter);
+	}
+
+	filters
+}